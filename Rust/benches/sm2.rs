@@ -0,0 +1,73 @@
+#![allow(deprecated)]
+
+
+// cargo bench --bench sm2
+use std::hint::black_box;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use num_bigint::BigUint;
+use sm_crypto::sm2::sm2::*;
+
+
+fn bench_sm2_point_mul(c: &mut Criterion) {
+  let sm2_obj = Sm2::new();
+  let private_key = sm2_obj.generate_key_pair_hex(None).unwrap().private_key;
+  let d = BigUint::parse_bytes(private_key.as_bytes(), 16).unwrap();
+  let p_a = sm2_obj.ec_gpoint.mul(&d);
+  // 首次调用生成预计算表，不计入
+  sm2_obj.furnish_public_key_from_private_key(&private_key).unwrap();
+
+  let mut group = c.benchmark_group("sm2_point_mul");
+  group.bench_function("double_and_add", |b| b.iter(|| sm2_obj.ec_gpoint.mul_vartime(black_box(&d))));
+  group.bench_function("montgomery_ladder", |b| b.iter(|| sm2_obj.ec_gpoint.mul(black_box(&d))));
+  group.bench_function("gpoint_table", |b| {
+    b.iter(|| sm2_obj.furnish_public_key_from_private_key(black_box(&private_key)).unwrap())
+  });
+  group.bench_function("mul_add_apart", |b| {
+    b.iter(|| sm2_obj.ec_gpoint.mul_vartime(black_box(&d)).add(&p_a.mul_vartime(black_box(&d))))
+  });
+  group.bench_function("mul_add_wnaf", |b| {
+    b.iter(|| sm2_obj.ec_gpoint.mul_add_vartime(black_box(&d), &p_a, black_box(&d)))
+  });
+  group.finish();
+}
+
+
+fn bench_sm2_sign_verify(c: &mut Criterion) {
+  let sm2_obj = Sm2::new();
+  let key_pair = sm2_obj.generate_key_pair_hex(None).unwrap();
+  let (private_key, public_key) = (key_pair.private_key, key_pair.public_key);
+  let sign = sm2_obj.sign("hello world", &private_key, true, true, None, None).unwrap();
+
+  c.bench_function("sm2_sign", |b| {
+    b.iter(|| sm2_obj.sign(black_box("hello world"), &private_key, true, true, None, None).unwrap())
+  });
+  c.bench_function("sm2_verify", |b| {
+    b.iter(|| assert!(sm2_obj.verify(black_box("hello world"), &sign, &public_key, true, true, None).unwrap()))
+  });
+}
+
+
+fn bench_sm2_verify_batch(c: &mut Criterion) {
+  let sm2_obj = Sm2::new();
+  let key_pair = sm2_obj.generate_key_pair_hex(None).unwrap();
+  let (private_key, public_key) = (key_pair.private_key, key_pair.public_key);
+  let sign = sm2_obj.sign("hello world", &private_key, true, true, None, None).unwrap();
+
+  let mut group = c.benchmark_group("sm2_verify_batch");
+  for count in [16usize, 64, 256] {
+    let entries = vec![Sm2BatchEntry {
+      message: b"hello world",
+      signature: &sign,
+      public_key: &public_key,
+      user_id: None,
+    }; count];
+    group.bench_with_input(BenchmarkId::from_parameter(count), &entries, |b, entries| {
+      b.iter(|| assert!(sm2_obj.verify_batch(black_box(entries), true).is_empty()))
+    });
+  }
+  group.finish();
+}
+
+
+criterion_group!(benches, bench_sm2_point_mul, bench_sm2_sign_verify, bench_sm2_verify_batch);
+criterion_main!(benches);
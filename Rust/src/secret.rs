@@ -0,0 +1,82 @@
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+#[cfg(feature = "zeroize")]
+use num_bigint::BigUint;
+
+
+/// 可擦除的秘密值
+pub(crate) trait Wipe {
+  /// 将内容所占用的内存覆写为0
+  fn wipe(&mut self);
+}
+
+
+#[cfg(feature = "zeroize")]
+macro_rules! impl_wipe_by_zeroize {
+  ($($t:ty),*) => {
+    $(
+      impl Wipe for $t {
+        fn wipe(&mut self) {
+          zeroize::Zeroize::zeroize(self);
+        }
+      }
+    )*
+  };
+}
+
+#[cfg(feature = "zeroize")]
+impl_wipe_by_zeroize!(Vec<u8>, Vec<u32>, Vec<u64>, String);
+
+
+#[cfg(feature = "zeroize")]
+impl Wipe for BigUint {
+  fn wipe(&mut self) {
+    // BigUint未提供可变的底层数组，assign_from_slice会在原有容量上以0覆写全部数位，再截断为0
+    let digitl = self.iter_u32_digits().len();
+    self.assign_from_slice(&vec![0u32; digitl]);
+    std::sync::atomic::compiler_fence(std::sync::atomic::Ordering::SeqCst);
+  }
+}
+
+
+#[cfg(not(feature = "zeroize"))]
+impl<T> Wipe for T {
+  fn wipe(&mut self) {}
+}
+
+
+/// 离开作用域时擦除内容的秘密值，用于私钥、随机数`k`、共享点和轮密钥等；
+/// 未开启`zeroize`特性时仅为普通的包装
+#[derive(Clone, Default)]
+pub(crate) struct Secret<T: Wipe>(pub T);
+
+
+impl<T: Wipe> Deref for Secret<T> {
+  type Target = T;
+
+  fn deref(&self) -> &T {
+    &self.0
+  }
+}
+
+
+impl<T: Wipe> DerefMut for Secret<T> {
+  fn deref_mut(&mut self) -> &mut T {
+    &mut self.0
+  }
+}
+
+
+impl<T: Wipe> Drop for Secret<T> {
+  fn drop(&mut self) {
+    self.0.wipe();
+  }
+}
+
+
+impl<T: Wipe> fmt::Debug for Secret<T> {
+  /// 不输出秘密值本身
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str("Secret(..)")
+  }
+}
@@ -1,103 +1,168 @@
-use std::rc::Rc;
-use num_bigint::{BigInt, BigUint, ToBigInt, ToBigUint};
-use num_traits::{One, Zero};
+#![allow(deprecated)]
 
 
-/// 计算`dial`在`mod inv_mod`下的乘法逆元，即$dial ^ {-1} \mod inv\_mod$。
-pub fn calculate_inverse_mod(dial: &BigUint, inv_mod: &BigUint) -> Option<BigUint> {
-  let mut a = inv_mod.to_bigint().unwrap();
-  let mut b = dial.to_bigint().unwrap();
-  let mut x0 = BigInt::zero();
-  let mut x1 = BigInt::one();
+use std::sync::Arc;
+use num_bigint::{BigUint, RandBigInt};
+use num_traits::{Num, One, Zero};
+use crate::secret::Secret;
+use crate::sm2::field::{ct_eq_mask, ct_select, ct_swap, Limbs, MontField};
+use crate::sm2::sm2::Sm2Error;
 
-  while b != BigInt::zero() {
-    let q = &a / &b;
-    let r = &a % &b;
-    a = b;
-    b = r;
 
-    let tmp = x0 - &q * &x1;
-    x0 = x1;
-    x1 = tmp;
-  }
-
-  if a != BigInt::one() {
-    return None; // 无逆元
-  }
-
-  // 可能为负，调整为正模
-  let result = (x0 % inv_mod.to_bigint().unwrap() +
-    inv_mod.to_bigint().unwrap()) % inv_mod.to_bigint().unwrap();
-  result.to_biguint()
-}
-
-
-/// 椭圆曲线域元素
-/// - `q`: `BigUint`，椭圆曲线所基于的素数字段（有限域）的大素数模数
-/// - `x`: `BigUint`，有限域中的一个元素（模`q`后的整数）
+/// 椭圆曲线域元素，以Montgomery形式保存
+/// - field: 椭圆曲线所基于的素数字段（有限域）
+/// - x: 有限域中的一个元素（Montgomery形式）
 #[derive(Clone, Debug)]
 pub struct ECFieldElementFp {
-  pub q: BigUint,
-  pub x: BigUint,
+  field: Arc<MontField>,
+  x: Limbs,
 }
 
 
 impl ECFieldElementFp {
-  /// 构造一个椭圆曲线域元素，不会对`q`是否是素数做检查
+  /// 构造一个椭圆曲线域元素，`x`会先对模数取模
   /// ## Parameters
-  /// - q: 椭圆曲线所基于的素数字段（有限域）的大素数模数
-  /// - x: 有限域中的一个元素（模`q`后的整数）
-  pub fn new(q: BigUint, x: BigUint) -> Self {
+  /// - field: 椭圆曲线所基于的素数字段（有限域）
+  /// - x: 有限域中的一个元素
+  pub fn new(field: Arc<MontField>, x: &BigUint) -> Self {
     Self {
-      x: x % &q,
-      q,
+      x: field.furnish_limbs(x),
+      field,
+    }
+  }
+
+  /// 由Montgomery形式的limbs构造
+  fn from_limbs(field: &Arc<MontField>, x: Limbs) -> Self {
+    Self {
+      field: field.clone(),
+      x,
     }
   }
 
   /// 返回有限域元素的整数值（对阶取模）
   pub fn furnish_item_big_uint(&self) -> BigUint {
-    self.x.clone()
+    self.field.furnish_big_uint(&self.x)
   }
 
   /// 取反
   pub fn negate(&self) -> Self {
-    // this.x.negate().mod(this.q)
-    // js的BigInteger.mod()始终返回正数余数，此处在new()的时候已经约定了$x \in [0, q - 1]$。
-    Self::new(self.q.clone(), (&self.q - &self.x) % &self.q)
+    Self::from_limbs(&self.field, self.field.neg(&self.x))
   }
 
   /// 加法
   pub fn add(&self, other: &Self) -> Self {
-    assert_eq!(self.q, other.q);
-    Self::new(self.q.clone(), (&self.x + &other.x) % &self.q)
+    assert_eq!(self.field, other.field);
+    Self::from_limbs(&self.field, self.field.add(&self.x, &other.x))
   }
 
   /// 减法
   pub fn sub(&self, other: &Self) -> Self {
-    assert_eq!(self.q, other.q);
-    Self::new(self.q.clone(), (&self.x + &self.q - &other.x) % &self.q)
+    assert_eq!(self.field, other.field);
+    Self::from_limbs(&self.field, self.field.sub(&self.x, &other.x))
   }
 
   /// 乘法
   pub fn mul(&self, other: &Self) -> Self {
-    assert_eq!(self.q, other.q);
-    Self::new(self.q.clone(), (&self.x * &other.x) % &self.q)
+    assert_eq!(self.field, other.field);
+    Self::from_limbs(&self.field, self.field.mul(&self.x, &other.x))
   }
 
   /// 除法
   pub fn dvd(&self, other: &Self) -> Self {
-    let inv = calculate_inverse_mod(&other.x, &self.q).expect("No modular inverse");
-    Self::new(self.q.clone(), (&self.x * inv) % &self.q)
+    assert!(!self.field.is_zero(&other.x), "No modular inverse");
+    Self::from_limbs(&self.field, self.field.mul(&self.x, &self.field.inv(&other.x)))
   }
 
   /// 平方
   pub fn sqr(&self) -> Self {
-    Self::new(self.q.clone(), (&self.x * &self.x) % &self.q)
+    Self::from_limbs(&self.field, self.field.sqr(&self.x))
   }
 
-  /// 平方根
+  /// 幂运算，用于求平方根
   pub fn modpow(&self, exp: &BigUint) -> Self {
-    Self::new(self.q.clone(), self.x.modpow(exp, &self.q))
+    Self::from_limbs(&self.field, self.field.pow(&self.x, exp))
+  }
+
+  /// 平方根，非二次剩余时返回None
+  ///
+  /// 对$p \equiv 3 \mod 4$直接计算$x ^ {(p + 1) / 4}$，否则使用Tonelli-Shanks算法；
+  /// 求得的根均经平方验证。仅用于公开数据（如压缩公钥），不是常数时间的。
+  pub fn sqrt(&self) -> Option<Self> {
+    let q = self.field.modulus();
+    let root = if q.bit(0) && q.bit(1) {
+      self.modpow(&((&q >> 2u32) + BigUint::one()))
+    } else {
+      self.sqrt_tonelli_shanks(&q)
+    };
+    if root.sqr() == *self { Some(root) } else { None }
+  }
+
+  /// Tonelli-Shanks算法，结果未经验证
+  fn sqrt_tonelli_shanks(&self, q: &BigUint) -> Self {
+    let one = Self::from_limbs(&self.field, self.field.one());
+    if self.field.is_zero(&self.x) {
+      return self.clone();
+    }
+
+    // $p - 1 = c \cdot 2 ^ s$，c为奇数
+    let q_minus1 = q - 1u8;
+    let s = q_minus1.trailing_zeros().unwrap_or(0);
+    let c = &q_minus1 >> s;
+    // 寻找一个二次非剩余z，即$z ^ {(p - 1) / 2} = -1$
+    let minus_one = one.negate();
+    let half = &q_minus1 >> 1u32;
+    let mut z = one.add(&one);
+    while z.modpow(&half) != minus_one {
+      z = z.add(&one);
+    }
+
+    let mut m = s;
+    let mut b = z.modpow(&c);
+    let mut t = self.modpow(&c);
+    let mut root = self.modpow(&((&c + 1u8) >> 1u32));
+    while t != one {
+      // 最小的i使得$t ^ {2 ^ i} = 1$；非二次剩余时不存在，直接返回由调用方验证
+      let mut i = 0;
+      let mut t2i = t.clone();
+      while t2i != one {
+        t2i = t2i.sqr();
+        i += 1;
+        if i == m {
+          return root;
+        }
+      }
+      for _ in 0..m - i - 1 {
+        b = b.sqr();
+      }
+      m = i;
+      root = root.mul(&b);
+      b = b.sqr();
+      t = t.mul(&b);
+    }
+    root
+  }
+
+  /// 是否为0
+  pub fn judge_zero(&self) -> bool {
+    self.field.is_zero(&self.x)
+  }
+
+  /// RFC 9380的sgn0，即整数值的最低位
+  pub fn sgn0(&self) -> bool {
+    self.field.mul(&self.x, &[1, 0, 0, 0])[0] & 1 == 1
+  }
+
+  /// 相等判断，逐limb比较而不提前返回
+  pub fn ct_eq(&self, other: &Self) -> bool {
+    assert_eq!(self.field, other.field);
+    let diff = self.x.iter().zip(other.x.iter()).fold(0u64, |diff, (xi, yi)| diff | (xi ^ yi));
+    ct_eq_mask(diff, 0) != 0
+  }
+
+  /// 按条件选择，choice为true时取other，为false时取self，不产生分支
+  pub fn conditional_select(&self, other: &Self, choice: bool) -> Self {
+    assert_eq!(self.field, other.field);
+    Self::from_limbs(&self.field, ct_select(&self.x, &other.x, 0u64.wrapping_sub(choice as u64)))
   }
 }
 
@@ -106,7 +171,7 @@ impl PartialEq for ECFieldElementFp {
   /// 判断两个椭圆曲线域元素是否相等
   fn eq(&self, other: &Self) -> bool {
     // this.q.equals(other.q) && this.x.equals(other.x)
-    self.q == other.q && self.x == other.x
+    self.field == other.field && self.x == other.x
   }
 }
 
@@ -114,187 +179,207 @@ impl PartialEq for ECFieldElementFp {
 impl Eq for ECFieldElementFp {}
 
 
-/// 椭圆曲线点
-#[derive(Clone, Debug)]
-pub struct ECPointFp {
-  pub curve: Rc<ECCurveFp>,
-  x: Option<ECFieldElementFp>,
-  y: Option<ECFieldElementFp>,
-  z: BigUint,
-  zinv: Option<BigUint>,
+/// 仿射坐标下的椭圆曲线点$(x, y)$，构造后不可变
+/// - curve: 点所在的椭圆曲线
+/// - coords: 仿射坐标，无穷远点为None
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AffinePoint {
+  pub curve: Arc<ECCurveFp>,
+  coords: Option<(ECFieldElementFp, ECFieldElementFp)>,
 }
 
 
-impl ECPointFp {
-  pub fn new(
-    curve: Rc<ECCurveFp>, x: Option<ECFieldElementFp>, y: Option<ECFieldElementFp>, z: Option<BigUint>,
-  ) -> Self {
+impl AffinePoint {
+  /// 由仿射坐标构造点，不检查点是否在曲线上
+  pub fn new(curve: Arc<ECCurveFp>, x: ECFieldElementFp, y: ECFieldElementFp) -> Self {
     Self {
       curve,
-      x,
-      y,
-      z: z.unwrap_or_else(BigUint::one),
-      zinv: None,
+      coords: Some((x, y)),
     }
   }
 
-  pub fn furnish_x(&mut self) -> Option<ECFieldElementFp> {
-    let x = self.x.as_ref()?.furnish_item_big_uint();
-    // z == 1，当前点是仿射形式，直接返回
-    if self.z == BigUint::one() {
-      return Some(self.x.clone()?);
+  /// 无穷远点
+  pub fn infty(curve: Arc<ECCurveFp>) -> Self {
+    Self {
+      curve,
+      coords: None,
     }
+  }
 
-    if self.zinv.is_none() {
-      self.zinv = calculate_inverse_mod(&self.z, &self.curve.q)
-    }
+  /// x坐标，无穷远点返回None
+  pub fn furnish_x(&self) -> Option<&ECFieldElementFp> {
+    self.coords.as_ref().map(|(x, _)| x)
+  }
 
-    let zinv = self.zinv.as_ref()?;
-    Some(self.curve.furnish_ec_field_from_biguint((x * zinv) % &self.curve.q))
+  /// y坐标，无穷远点返回None
+  pub fn furnish_y(&self) -> Option<&ECFieldElementFp> {
+    self.coords.as_ref().map(|(_, y)| y)
   }
 
-  pub fn furnish_y(&mut self) -> Option<ECFieldElementFp> {
-    let y = self.y.as_ref()?.furnish_item_big_uint();
-    // z == 1，当前点是仿射形式，直接返回
-    if self.z == BigUint::one() {
-      return Some(self.y.clone()?);
+  /// 是否是无穷远点
+  pub fn judge_infty(&self) -> bool {
+    self.coords.is_none()
+  }
+
+  /// 转为射影坐标$(x : y : 1)$
+  pub fn to_projective(&self) -> ProjectivePoint {
+    match &self.coords {
+      | Some((x, y)) => ProjectivePoint::from_affine(self.curve.clone(), x, y),
+      | None => self.curve.furnish_infty(),
     }
+  }
+}
 
-    if self.zinv.is_none() {
-      self.zinv = calculate_inverse_mod(&self.z, &self.curve.q)
+
+/// 射影坐标下的椭圆曲线点$(X : Y : Z)$，对应仿射坐标$(X / Z, Y / Z)$，$Z = 0$时为无穷远点；
+/// 同一点有多种表示，需经`to_affine`规范化后才能读取坐标
+#[derive(Clone, Debug)]
+pub struct ProjectivePoint {
+  pub curve: Arc<ECCurveFp>,
+  x: Limbs,
+  y: Limbs,
+  z: Limbs,
+}
+
+
+impl ProjectivePoint {
+  /// 由射影坐标构造点
+  fn new(curve: Arc<ECCurveFp>, x: Limbs, y: Limbs, z: Limbs) -> Self {
+    Self {
+      curve,
+      x,
+      y,
+      z,
     }
+  }
 
-    let zinv = self.zinv.as_ref()?;
-    Some(self.curve.furnish_ec_field_from_biguint((y * zinv) % &self.curve.q))
+  /// 由仿射坐标构造点，不检查点是否在曲线上
+  pub fn from_affine(curve: Arc<ECCurveFp>, x: &ECFieldElementFp, y: &ECFieldElementFp) -> Self {
+    let z = curve.field.one();
+    Self::new(curve, x.x, y.x, z)
+  }
+
+  /// 规范化为仿射坐标，$Z \neq 1$时需要一次求逆
+  pub fn to_affine(&self) -> AffinePoint {
+    if self.judge_infty() {
+      return AffinePoint::infty(self.curve.clone());
+    }
+    let field = &self.curve.field;
+    let (x, y) = if self.z == field.one() {
+      (self.x, self.y)
+    } else {
+      let zinv = field.inv(&self.z);
+      (field.mul(&self.x, &zinv), field.mul(&self.y, &zinv))
+    };
+    AffinePoint::new(
+      self.curve.clone(),
+      ECFieldElementFp::from_limbs(field, x),
+      ECFieldElementFp::from_limbs(field, y),
+    )
   }
 
   /// 是否是无穷远点
   pub fn judge_infty(&self) -> bool {
-    if self.x.is_none() && self.y.is_none() {
-      return true;
-    }
-    // z == 0且y != 0，兼容投影坐标
-    if self.z.is_zero() {
-      if let Some(y) = &self.y {
-        return !y.furnish_item_big_uint().is_zero();
-      }
-    }
-    false
+    self.curve.field.is_zero(&self.z)
   }
 
   /// 取反，即`x`轴对称点
   pub fn negate(&self) -> Self {
-    Self::new(self.curve.clone(), self.x.clone(), self.y.as_ref().map(|y| y.negate()), Some(self.z.clone()))
+    Self::new(self.curve.clone(), self.x, self.curve.field.neg(&self.y), self.z)
   }
 
-  /// 自增
+  /// 自增，采用Renes-Costello-Batina的完备倍点公式（任意`a`），无穷远点与2阶点无需分支
   pub fn twice(&self) -> Self {
-    if self.judge_infty() {
-      return self.clone();
-    }
-    if self.y.as_ref().unwrap().furnish_item_big_uint().is_zero() {
-      return self.curve.furnish_infty();
-    }
-
-    let x1 = self.x.as_ref().unwrap().furnish_item_big_uint();
-    let y1 = self.y.as_ref().unwrap().furnish_item_big_uint();
-    let z1 = &self.z;
-    let q = &self.curve.q;
-    let a = self.curve.a.furnish_item_big_uint();
-
-    // w1 = x1.square().multiply(THREE).add(a.multiply(z1.square())).mod(q)
-    let w1 = ((&x1 * &x1 * 3.to_biguint().unwrap()) + a * z1 * z1) % q;
-    // w2 = y1.shiftLeft(1).multiply(z1).mod(q)
-    let w2 = (&y1 << 1) * z1 % q;
-    // w3 = y1.square().mod(q)
-    let w3 = &y1 * &y1 % q;
-    // w4 = w3.multiply(x1).multiply(z1).mod(q)
-    let w4 = (&w3 * x1 * z1) % q;
-    // w5 = w2.square().mod(q)
-    let w5 = (&w2 * &w2) % q;
-    // w6 = w1.square().subtract(w4.shiftLeft(3)).mod(q)
-    let w6 = (&w1 * &w1 + q - (&w4 << 3) % q) % q;
-
-    // x3 = w2.multiply(w6).mod(q)
-    let x3 = (&w2 * &w6) % q;
-    // y3 = w1.multiply(w4.shiftLeft(2).subtract(w6)).subtract(w5.shiftLeft(1).multiply(w3)).mod(q)
-    let y3 = (&w1 * ((&w4 << 2) + q - &w6) + q - ((&w5 << 1) * &w3) % q) % q;
-    // z3 = w2.multiply(w5).mod(q)
-    let z3 = (&w2 * &w5) % q;
-
-    Self::new(
-      self.curve.clone(),
-      Some(self.curve.furnish_ec_field_from_biguint(x3)),
-      Some(self.curve.furnish_ec_field_from_biguint(y3)),
-      Some(z3),
-    )
+    let fp = &self.curve.field;
+    let (a, b3) = (&self.curve.a.x, &self.curve.b3);
+    let (x, y, z) = (&self.x, &self.y, &self.z);
+
+    let t0 = fp.mul(x, x);
+    let t1 = fp.mul(y, y);
+    let t2 = fp.mul(z, z);
+    let t3 = fp.double(&fp.mul(x, y));
+    let z3 = fp.double(&fp.mul(x, z));
+    let x3 = fp.mul(a, &z3);
+    let y3 = fp.add(&x3, &fp.mul(b3, &t2));
+    let x3 = fp.sub(&t1, &y3);
+    let y3 = fp.mul(&x3, &fp.add(&t1, &y3));
+    let x3 = fp.mul(&t3, &x3);
+    let z3 = fp.mul(b3, &z3);
+    let t2 = fp.mul(a, &t2);
+    let t3 = fp.add(&fp.mul(a, &fp.sub(&t0, &t2)), &z3);
+    let t0 = fp.add(&fp.add(&fp.double(&t0), &t0), &t2);
+    let y3 = fp.add(&y3, &fp.mul(&t0, &t3));
+    let t2 = fp.double(&fp.mul(y, z));
+    let x3 = fp.sub(&x3, &fp.mul(&t2, &t3));
+    let z3 = fp.double(&fp.double(&fp.mul(&t2, &t1)));
+
+    Self::new(self.curve.clone(), x3, y3, z3)
   }
 
-  /// 相加
+  /// 相加，采用Renes-Costello-Batina的完备加法公式（任意`a`），
+  /// 对素数阶曲线上的任意两点（含相等点、互逆点与无穷远点）都成立，无需分支
   pub fn add(&self, other: &Self) -> Self {
-    if self.judge_infty() {
-      return other.clone();
-    }
-    if other.judge_infty() {
-      return self.clone();
-    }
+    let fp = &self.curve.field;
+    let (a, b3) = (&self.curve.a.x, &self.curve.b3);
+    let (x1, y1, z1) = (&self.x, &self.y, &self.z);
+    let (x2, y2, z2) = (&other.x, &other.y, &other.z);
+
+    let t0 = fp.mul(x1, x2);
+    let t1 = fp.mul(y1, y2);
+    let t2 = fp.mul(z1, z2);
+    // t3 = X1 * Y2 + X2 * Y1
+    let t3 = fp.sub(&fp.mul(&fp.add(x1, y1), &fp.add(x2, y2)), &fp.add(&t0, &t1));
+    // t4 = X1 * Z2 + X2 * Z1
+    let t4 = fp.sub(&fp.mul(&fp.add(x1, z1), &fp.add(x2, z2)), &fp.add(&t0, &t2));
+    // t5 = Y1 * Z2 + Y2 * Z1
+    let t5 = fp.sub(&fp.mul(&fp.add(y1, z1), &fp.add(y2, z2)), &fp.add(&t1, &t2));
+    let z3 = fp.add(&fp.mul(b3, &t2), &fp.mul(a, &t4));
+    let x3 = fp.sub(&t1, &z3);
+    let z3 = fp.add(&t1, &z3);
+    let y3 = fp.mul(&x3, &z3);
+    let t1 = fp.add(&fp.double(&t0), &t0);
+    let t2 = fp.mul(a, &t2);
+    let t4 = fp.mul(b3, &t4);
+    let t1 = fp.add(&t1, &t2);
+    let t2 = fp.mul(a, &fp.sub(&t0, &t2));
+    let t4 = fp.add(&t4, &t2);
+    let y3 = fp.add(&y3, &fp.mul(&t1, &t4));
+    let x3 = fp.sub(&fp.mul(&t3, &x3), &fp.mul(&t5, &t4));
+    let z3 = fp.add(&fp.mul(&t5, &z3), &fp.mul(&t3, &t1));
+
+    Self::new(self.curve.clone(), x3, y3, z3)
+  }
 
-    let x1 = self.x.as_ref().unwrap().furnish_item_big_uint();
-    let y1 = self.y.as_ref().unwrap().furnish_item_big_uint();
-    let z1 = &self.z;
-    let x2 = other.x.as_ref().unwrap().furnish_item_big_uint();
-    let y2 = other.y.as_ref().unwrap().furnish_item_big_uint();
-    let z2 = &other.z;
-    let q = &self.curve.q;
-
-    // w1 = x1.multiply(z2).mod(q)
-    let w1 = (x1 * z2) % q;
-    // w2 = x2.multiply(z1).mod(q)
-    let w2 = (x2 * z1) % q;
-    // w3 = w1.subtract(w2)
-    let w3 = (&w1 + q - &w2) % q;
-    // w4 = y1.multiply(z2).mod(q)
-    let w4 = (y1 * z2) % q;
-    // w5 = y2.multiply(z1).mod(q)
-    let w5 = (y2 * z1) % q;
-    // w6 = w4.subtract(w5)
-    let w6 = (&w4 + q - &w5) % q;
-
-    if w3.is_zero() {
-      if w6.is_zero() {
-        return self.twice();
-      }
-      return self.curve.furnish_infty();
+  /// 按掩码交换两点的坐标，mask为全1时交换，为0时保持不变，不产生分支
+  fn conditional_swap(&mut self, other: &mut Self, mask: u64) {
+    ct_swap(&mut self.x, &mut other.x, mask);
+    ct_swap(&mut self.y, &mut other.y, mask);
+    ct_swap(&mut self.z, &mut other.z, mask);
+  }
+
+  /// 倍点，用于私钥、随机数`k`等秘密标量；
+  /// 采用Montgomery阶梯，按固定的比特数（不少于256）逐位执行一次加法与一次倍点，
+  /// 以掩码交换代替按标量比特的分支，运算次数与访存模式均与标量的取值无关
+  pub fn mul(&self, other: &BigUint) -> Self {
+    let mut digits = Secret(other.to_u64_digits());
+    let digitl = digits.len().max(4);
+    digits.resize(digitl, 0);
+
+    let mut ec_point_r0 = self.curve.furnish_infty();
+    let mut ec_point_r1 = self.clone();
+    for i in (0..digitl * 64).rev() {
+      let mask = 0u64.wrapping_sub((digits[i / 64] >> (i % 64)) & 1);
+      ec_point_r0.conditional_swap(&mut ec_point_r1, mask);
+      ec_point_r1 = ec_point_r0.add(&ec_point_r1);
+      ec_point_r0 = ec_point_r0.twice();
+      ec_point_r0.conditional_swap(&mut ec_point_r1, mask);
     }
 
-    // w7 = w1.add(w2)
-    let w7 = (&w1 + &w2) % q;
-    // w8 = z1.multiply(z2).mod(q)
-    let w8 = (z1 * z2) % q;
-    // w9 = w3.square().mod(q)
-    let w9 = (&w3 * &w3) % q;
-    // w10 = w3.multiply(w9).mod(q)
-    let w10 = (&w3 * &w9) % q;
-    // w11 = w8.multiply(w6.square()).subtract(w7.multiply(w9)).mod(q)
-    let w11 = (&w8 * &w6 * &w6 + q - (&w7 * &w9) % q) % q;
-
-    // x3 = w3.multiply(w11).mod(q)
-    let x3 = (&w3 * &w11) % q;
-    // y3 = w6.multiply(w9.multiply(w1).subtract(w11)).subtract(w4.multiply(w10)).mod(q)
-    let y3 = (&w6 * ((&w9 * &w1 + q - &w11) % q) + q - (&w4 * &w10) % q) % q;
-    // z3 = w10.multiply(w8).mod(q)
-    let z3 = (&w10 * &w8) % q;
-
-    Self::new(
-      self.curve.clone(),
-      Some(self.curve.furnish_ec_field_from_biguint(x3)),
-      Some(self.curve.furnish_ec_field_from_biguint(y3)),
-      Some(z3),
-    )
+    ec_point_r0
   }
 
-  /// 倍点
-  pub fn mul(&self, other: &BigUint) -> Self {
+  /// 倍点，仅用于公开标量（如验签中的`s`、`t`与阶`n`），按标量比特分支，运算时间与标量相关
+  pub fn mul_vartime(&self, other: &BigUint) -> Self {
     if self.judge_infty() {
       return self.clone();
     }
@@ -302,7 +387,7 @@ impl ECPointFp {
       return self.curve.furnish_infty();
     }
 
-    let k3 = other * 3.to_biguint().unwrap();
+    let k3 = other * 3u8;
     let mut ec_point_q = self.clone();
     let ec_point_neg = self.negate();
 
@@ -319,51 +404,190 @@ impl ECPointFp {
 
     ec_point_q
   }
-}
 
+  /// 双标量倍点$[k]P + [l]Q$，仅用于公开标量（如验签中的`s`、`t`）；
+  /// 两个标量分别取宽度为5的wNAF，共用一条倍点链交错相加，倍点次数减半
+  /// ## Parameters
+  /// - k: 当前点的标量
+  /// - other: 另一个点Q
+  /// - l: Q的标量
+  pub fn mul_add_vartime(&self, k: &BigUint, other: &Self, l: &BigUint) -> Self {
+    Self::mul_add_from_tables(&self.furnish_odd_multiples(), k, &other.furnish_odd_multiples(), l)
+  }
 
-impl PartialEq for ECPointFp {
-  fn eq(&self, other: &Self) -> bool {
-    if Rc::ptr_eq(&self.curve, &other.curve) {
-      return self.x == other.x && self.y == other.y;
+  /// 由两点的奇数倍点表计算$[k]P + [l]Q$，供同一点参与多次运算时复用倍点表（如批量验签中的基点G）
+  /// ## Parameters
+  /// - table_k: P的奇数倍点表，由`furnish_odd_multiples`生成
+  /// - k: P的标量
+  /// - table_l: Q的奇数倍点表
+  /// - l: Q的标量
+  pub fn mul_add_from_tables(table_k: &[Self], k: &BigUint, table_l: &[Self], l: &BigUint) -> Self {
+    let naf_k = furnish_wnaf(k, WNAF_WIDTH);
+    let naf_l = furnish_wnaf(l, WNAF_WIDTH);
+
+    let mut ec_point_q = table_k[0].curve.furnish_infty();
+    for i in (0..naf_k.len().max(naf_l.len())).rev() {
+      ec_point_q = ec_point_q.twice();
+      for (naf, table) in [(&naf_k, table_k), (&naf_l, table_l)] {
+        match naf.get(i).copied().unwrap_or(0) {
+          | 0 => {}
+          | digit if digit > 0 => ec_point_q = ec_point_q.add(&table[digit as usize / 2]),
+          | digit => ec_point_q = ec_point_q.add(&table[digit.unsigned_abs() as usize / 2].negate()),
+        }
+      }
     }
-    if self.judge_infty() {
-      return other.judge_infty();
+
+    ec_point_q
+  }
+
+  /// 奇数倍点表$P, [3]P, [5]P, ..., [2 ^ {w - 1} - 1]P$
+  pub fn furnish_odd_multiples(&self) -> Vec<Self> {
+    let ec_point_dbl = self.twice();
+    let mut table = Vec::with_capacity(1 << (WNAF_WIDTH - 2));
+    table.push(self.clone());
+    for i in 1..(1 << (WNAF_WIDTH - 2)) {
+      let ec_point_next = table[i - 1].add(&ec_point_dbl);
+      table.push(ec_point_next);
     }
-    if other.judge_infty() {
-      return self.judge_infty();
+    table
+  }
+}
+
+
+/// wNAF的窗口宽度
+const WNAF_WIDTH: u32 = 5;
+
+
+/// 计算标量的宽度为w的非相邻形式（低位在前），非零位均为绝对值小于$2 ^ {w - 1}$的奇数，
+/// 且任意w个连续位中至多一个非零
+/// ## Parameters
+/// - k: 标量
+/// - w: 窗口宽度，取值范围[2, 8]
+fn furnish_wnaf(k: &BigUint, w: u32) -> Vec<i8> {
+  let window = 1i64 << w;
+  let mut dial = k.clone();
+  let mut naf = Vec::with_capacity(dial.bits() as usize + 1);
+  while !dial.is_zero() {
+    let mut digit = 0i64;
+    if dial.bit(0) {
+      digit = (dial.iter_u64_digits().next().unwrap_or(0) & (window as u64 - 1)) as i64;
+      if digit >= window / 2 {
+        digit -= window;
+      }
+      if digit > 0 {
+        dial -= digit as u64;
+      } else {
+        dial += digit.unsigned_abs();
+      }
     }
+    naf.push(digit as i8);
+    dial >>= 1u32;
+  }
+  naf
+}
+
 
-    // u = other.y.toBigInteger().multiply(this.z).subtract(this.y.toBigInteger().multiply(other.z))
-    // v = other.x.toBigInteger().multiply(this.z).subtract(this.x.toBigInteger().multiply(other.z))
-    let q = &self.curve.q;
-    let x1 = self.x.as_ref().unwrap().furnish_item_big_uint();
-    let y1 = self.y.as_ref().unwrap().furnish_item_big_uint();
-    let z1 = &self.z;
-    let x2 = other.x.as_ref().unwrap().furnish_item_big_uint();
-    let y2 = other.y.as_ref().unwrap().furnish_item_big_uint();
-    let z2 = &other.z;
-
-    if (y2 * z1) % q != (y1 * z2) % q {
+impl PartialEq for ProjectivePoint {
+  /// 判断两点是否相等，与射影坐标的表示无关：$X_1 Z_2 = X_2 Z_1$且$Y_1 Z_2 = Y_2 Z_1$
+  fn eq(&self, other: &Self) -> bool {
+    if !Arc::ptr_eq(&self.curve, &other.curve) && self.curve != other.curve {
       return false;
     }
-    (x2 * z1) % q == (x1 * z2) % q
+    if self.judge_infty() || other.judge_infty() {
+      return self.judge_infty() && other.judge_infty();
+    }
+
+    let fp = &self.curve.field;
+    fp.mul(&self.x, &other.z) == fp.mul(&other.x, &self.z)
+      && fp.mul(&self.y, &other.z) == fp.mul(&other.y, &self.z)
   }
 }
 
 
-impl Eq for ECPointFp {}
+impl Eq for ProjectivePoint {}
+
+
+/// 窗口宽度（比特）
+const TABLE_WINDOW_BITS: usize = 4;
+/// 每个窗口的表项数
+const TABLE_WINDOW_SIZE: usize = 1 << TABLE_WINDOW_BITS;
+/// 覆盖256比特标量所需的窗口数
+const TABLE_WINDOWS: usize = 256 / TABLE_WINDOW_BITS;
+
+
+/// 固定点（如基点G）的预计算窗口表，第i个窗口保存$[j \cdot 16 ^ i]P$（$j = 0, 1, ..., 15$）的射影坐标；
+/// 只保存limbs而不引用曲线，可放入静态变量在多个`Sm2`实例间共享
+#[derive(Debug)]
+pub struct ECPointTable {
+  windows: Vec<[[Limbs; 3]; TABLE_WINDOW_SIZE]>,
+}
+
+
+impl ECPointTable {
+  /// 为固定点生成预计算表，共64个窗口、1024个点
+  /// ## Parameters
+  /// - point: 固定点
+  pub fn new(point: &ProjectivePoint) -> Self {
+    let mut windows = Vec::with_capacity(TABLE_WINDOWS);
+    let mut ec_point_base = point.clone();
+    for _ in 0..TABLE_WINDOWS {
+      let mut window = [[[0u64; 4]; 3]; TABLE_WINDOW_SIZE];
+      let mut ec_point_acc = point.curve.furnish_infty();
+      for entry in window.iter_mut() {
+        *entry = [ec_point_acc.x, ec_point_acc.y, ec_point_acc.z];
+        ec_point_acc = ec_point_acc.add(&ec_point_base);
+      }
+      windows.push(window);
+      // 循环结束时累加了16次，即下一窗口的基点$[16]P$
+      ec_point_base = ec_point_acc;
+    }
+    Self { windows }
+  }
+
+  /// 固定点的倍点$[k]P$，每个窗口只做一次加法而无需倍点；
+  /// 表项按掩码遍历整个窗口选出，运算次数与访存模式均与标量的取值无关
+  /// ## Parameters
+  /// - curve: 生成该表时固定点所在的曲线
+  /// - other: 标量，不超过256比特
+  pub fn mul(&self, curve: &Arc<ECCurveFp>, other: &BigUint) -> ProjectivePoint {
+    assert!(other.bits() <= 256, "scalar must be at most 256 bits");
+    let mut digits = Secret(other.to_u64_digits());
+    digits.resize(4, 0);
+
+    let digits_per_limb = 64 / TABLE_WINDOW_BITS;
+    let mut ec_point_q = curve.furnish_infty();
+    for (i, window) in self.windows.iter().enumerate() {
+      let digit = (digits[i / digits_per_limb] >> (i % digits_per_limb * TABLE_WINDOW_BITS))
+        & (TABLE_WINDOW_SIZE as u64 - 1);
+      let mut entry = [[0u64; 4]; 3];
+      for (j, candidate) in window.iter().enumerate() {
+        let mask = ct_eq_mask(j as u64, digit);
+        for (coord, candidate_coord) in entry.iter_mut().zip(candidate) {
+          *coord = ct_select(coord, candidate_coord, mask);
+        }
+      }
+      let [x, y, z] = entry;
+      ec_point_q = ec_point_q.add(&ProjectivePoint::new(curve.clone(), x, y, z));
+    }
+
+    ec_point_q
+  }
+}
 
 
 /// 椭圆曲线$y ^ 2 = x ^ 3 + a * x + b$
 /// - q: 椭圆曲线所基于的素数字段（有限域）的大素数模数
 /// - a: 椭圆曲线方程的`a`项
 /// - b: 椭圆曲线方程的`b`项
+/// - field: `q`上的有限域
+/// - b3: Montgomery形式的$3b$，用于完备加法公式
 #[derive(Debug)]
 pub struct ECCurveFp {
   pub q: BigUint,
   pub a: ECFieldElementFp,
   pub b: ECFieldElementFp,
+  field: Arc<MontField>,
+  b3: Limbs,
 }
 
 
@@ -373,72 +597,91 @@ impl ECCurveFp {
   /// - q: 椭圆曲线所基于的素数字段（有限域）的大素数模数
   /// - a: 椭圆曲线方程的`a`项
   /// - b: 椭圆曲线方程的`b`项
-  pub fn new(q: BigUint, a: BigUint, b: BigUint) -> Rc<Self> {
-    Rc::new(Self {
-      q: q.clone(),
-      a: ECFieldElementFp::new(q.clone(), a),
-      b: ECFieldElementFp::new(q, b),
+  pub fn new(q: BigUint, a: BigUint, b: BigUint) -> Arc<Self> {
+    let field = Arc::new(MontField::new(&q));
+    let b = ECFieldElementFp::new(field.clone(), &b);
+    let b3 = field.add(&field.double(&b.x), &b.x);
+    Arc::new(Self {
+      q,
+      a: ECFieldElementFp::new(field.clone(), &a),
+      b,
+      field,
+      b3,
     })
   }
 
-  /// 生成椭圆曲线的无穷远点
-  pub fn furnish_infty(self: &Rc<Self>) -> ECPointFp {
-    ECPointFp::new(self.clone(), None, None, None)
+  /// 坐标的字节数$\lceil \log_2 q / 8 \rceil$，公钥、签名和密文中的坐标均按该长度编码
+  pub fn furnish_coord_len(&self) -> usize {
+    (self.q.bits() as usize).div_ceil(8)
+  }
+
+  /// 生成椭圆曲线的无穷远点$(0 : 1 : 0)$
+  pub fn furnish_infty(self: &Arc<Self>) -> ProjectivePoint {
+    ProjectivePoint::new(self.clone(), self.field.zero(), self.field.one(), self.field.zero())
   }
 
   /// 生成椭圆曲线域元素
   /// ## Parameters
   /// - x: 有限域中的一个元素（模`q`后的整数）
   pub fn furnish_ec_field_from_biguint(&self, x: BigUint) -> ECFieldElementFp {
-    ECFieldElementFp::new(self.q.clone(), x)
+    ECFieldElementFp::new(self.field.clone(), &x)
   }
 
-  /// 解析十六进制串为椭圆曲线点
+  /// 解析十六进制串为椭圆曲线点，支持`00`、`02`/`03`、`04`和`06`/`07`前缀
+  ///
+  /// 压缩编码的x须为二次剩余，混合编码的前缀须与y的奇偶性一致；不检查非压缩编码的点是否在曲线上和点的阶。
   /// ## Parameters
-  /// - hex_talks: 十六进制串，且长度为奇数
-  pub fn decode_point_hex(self: &Rc<Self>, hex_talks: &str) -> Option<ECPointFp> {
-    let hex_byte1 = u8::from_str_radix(&hex_talks[..2], 16).ok()?;
-
-    match hex_byte1 {
-      | 0 => Some(self.furnish_infty()),
-      | 2 | 3 => {
-        let x = BigUint::parse_bytes(&hex_talks[2..].as_bytes(), 16)?;
-        let ec_field_ele_x = self.furnish_ec_field_from_biguint(x.clone());
-        // 对$p \equiv 3 \mod 4$，即存在正整数$u$，使得$p = 4u + 3$，计算
-        // $y = (\sqrt{x ^ 3 + ax + b} \mod p) ^ {u + 1} \mod p$。
-        let y = ec_field_ele_x
-          .mul(&ec_field_ele_x.sqr())
-          .add(&ec_field_ele_x.mul(&self.a))
-          .add(&self.b)
-          .furnish_item_big_uint()
-          // .modPow(this.q.divide(new BigInteger('4')).add(BigInteger.ONE), this.q)
-          .modpow(&((&self.q >> 2u32) + BigUint::one()), &self.q);
-        let mut ec_field_ele_y = self.furnish_ec_field_from_biguint(y);
-
-        // y.toBigInteger().mod(TWO)
-        let y_big_uint_mod2 = ec_field_ele_y.furnish_item_big_uint() & BigUint::one();
-        // 二进制最后1位不等于第1个字节 - 2则取反
-        if y_big_uint_mod2 != BigUint::from(hex_byte1 - 2) {
-          ec_field_ele_y = ec_field_ele_y.negate();
-        }
+  /// - hex_talks: 十六进制串
+  /// ## Returns
+  /// 椭圆曲线点；非十六进制返回Sm2Error::CodingError，长度错误返回Sm2Error::InvalidPublicKeyLength，
+  /// 坐标不小于p返回Sm2Error::CoordinateOutOfRange，压缩编码的x无对应的点返回Sm2Error::PointNotOnCurve，
+  /// 前缀未知或与y的奇偶性不一致返回Sm2Error::InvalidPublicKey
+  pub fn decode_point_hex(self: &Arc<Self>, hex_talks: &str) -> Result<ProjectivePoint, Sm2Error> {
+    if hex_talks.len() < 2 || !hex_talks.bytes().all(|byt| byt.is_ascii_hexdigit()) {
+      return Err(Sm2Error::CodingError);
+    }
+    let hex_byte1 = u8::from_str_radix(&hex_talks[..2], 16).map_err(|_| Sm2Error::CodingError)?;
+    let coord_hexl = 2 * self.furnish_coord_len();
+    let hex_talkl = match hex_byte1 {
+      | 0 => 2,
+      | 2 | 3 => 2 + coord_hexl,
+      | 4 | 6 | 7 => 2 + 2 * coord_hexl,
+      | _ => return Err(Sm2Error::InvalidPublicKey),
+    };
+    if hex_talks.len() != hex_talkl {
+      return Err(Sm2Error::InvalidPublicKeyLength);
+    }
+
+    let mut coords = Vec::with_capacity(2);
+    for coord_hex in hex_talks.as_bytes()[2..].chunks(coord_hexl) {
+      let coord = BigUint::parse_bytes(coord_hex, 16).ok_or(Sm2Error::CodingError)?;
+      if coord >= self.q {
+        return Err(Sm2Error::CoordinateOutOfRange);
+      }
+      coords.push(self.furnish_ec_field_from_biguint(coord));
+    }
 
-        Some(ECPointFp::new(Rc::clone(self), Some(ec_field_ele_x), Some(ec_field_ele_y), None))
+    match (hex_byte1, coords.as_slice()) {
+      | (0, _) => Ok(self.furnish_infty()),
+      | (2 | 3, [x]) => {
+        // $y ^ 2 = x ^ 3 + ax + b$，x不是二次剩余时曲线上不存在该点
+        let y = x.mul(&x.sqr()).add(&x.mul(&self.a)).add(&self.b).sqrt().ok_or(Sm2Error::PointNotOnCurve)?;
+        // 二进制最后1位不等于第1个字节 - 2则取反；y = 0时不存在奇数的根
+        let y = match (y.furnish_item_big_uint().bit(0), hex_byte1 == 3) {
+          | (odd, need_odd) if odd == need_odd => y,
+          | _ if y.furnish_item_big_uint().is_zero() => return Err(Sm2Error::PointNotOnCurve),
+          | _ => y.negate(),
+        };
+        Ok(ProjectivePoint::from_affine(Arc::clone(self), x, &y))
       }
-      | 4 | 6 | 7 => {
-        let hex_talkl = (hex_talks.len() - 2) / 2;
-        let x_hex = &hex_talks[2..2 + hex_talkl];
-        let y_hex = &hex_talks[2 + hex_talkl..];
-
-        let x = BigUint::parse_bytes(x_hex.as_bytes(), 16)?;
-        let y = BigUint::parse_bytes(y_hex.as_bytes(), 16)?;
-        Some(ECPointFp::new(
-          Rc::clone(self),
-          Some(self.furnish_ec_field_from_biguint(x)),
-          Some(self.furnish_ec_field_from_biguint(y)),
-          None,
-        ))
+      | (4 | 6 | 7, [x, y]) => {
+        // 混合编码的前缀携带y的奇偶性
+        if hex_byte1 != 4 && y.furnish_item_big_uint().bit(0) != (hex_byte1 == 7) {
+          return Err(Sm2Error::InvalidPublicKey);
+        }
+        Ok(ProjectivePoint::from_affine(Arc::clone(self), x, y))
       }
-      | _ => None,
+      | _ => Err(Sm2Error::InvalidPublicKey),
     }
   }
 }
@@ -452,3 +695,163 @@ impl PartialEq for ECCurveFp {
 
 
 impl Eq for ECCurveFp {}
+
+
+/// MOV条件检查的次数上限，取SEC 1中的100
+const CURVE_MOV_THRESHOLD: u32 = 100;
+/// Miller-Rabin素性检测的轮数
+const PRIME_TEST_ROUNDS: usize = 32;
+
+
+/// 素数域上短Weierstrass曲线$y ^ 2 = x ^ 3 + ax + b$的椭圆曲线域参数
+/// - p: 素数域的模数，不超过256比特
+/// - a: 椭圆曲线方程的`a`项
+/// - b: 椭圆曲线方程的`b`项
+/// - gx: 基点G的`x`坐标
+/// - gy: 基点G的`y`坐标
+/// - n: 基点G的阶
+/// - h: 余因子$\#E(F_p) / n$
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CurveParams {
+  pub p: BigUint,
+  pub a: BigUint,
+  pub b: BigUint,
+  pub gx: BigUint,
+  pub gy: BigUint,
+  pub n: BigUint,
+  pub h: BigUint,
+}
+
+
+impl CurveParams {
+  /// 由十六进制字符串构造域参数，不做校验
+  /// ## Returns
+  /// 域参数，十六进制串不合法则返回Sm2Error::CodingError
+  pub fn from_hex(
+    p: &str, a: &str, b: &str, gx: &str, gy: &str, n: &str, h: &str,
+  ) -> Result<Self, Sm2Error> {
+    let furnish = |hex_talks: &str| {
+      BigUint::from_str_radix(hex_talks, 16).map_err(|_| Sm2Error::CodingError)
+    };
+    Ok(Self {
+      p: furnish(p)?,
+      a: furnish(a)?,
+      b: furnish(b)?,
+      gx: furnish(gx)?,
+      gy: furnish(gy)?,
+      n: furnish(n)?,
+      h: furnish(h)?,
+    })
+  }
+
+  /// SM2推荐曲线sm2p256v1（GM/T 0003.5）
+  pub fn sm2p256v1() -> Self {
+    Self::from_hex(
+      "FFFFFFFEFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF00000000FFFFFFFFFFFFFFFF",
+      "FFFFFFFEFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF00000000FFFFFFFFFFFFFFFC",
+      "28E9FA9E9D9F5E344D5A9E4BCF6509A7F39789F515AB8F92DDBCBD414D940E93",
+      "32C4AE2C1F1981195F9904466A39C9948FE30BBFF2660BE1715A4589334C74C7",
+      "BC3736A2F4F6779C59BDCEE36B692153D0A9877CC62A474002DF32E52139F0A0",
+      "FFFFFFFEFFFFFFFFFFFFFFFFFFFFFFFF7203DF6B21C6052B53BBF40939D54123",
+      "1",
+    ).unwrap()
+  }
+
+  /// GM/T 0003附录中签名、密钥交换和加密示例所用的Fp-256曲线，仅用于测试
+  pub fn sm2_example_fp256() -> Self {
+    Self::from_hex(
+      "8542D69E4C044F18E8B92435BF6FF7DE457283915C45517D722EDB8B08F1DFC3",
+      "787968B4FA32C3FD2417842E73BBFEFF2F3C848B6831D7E0EC65228B3937E498",
+      "63E4C6D3B23B0C849CF84241484BFE48F61D59A5B16BA06E6E12D1DA27C5249A",
+      "421DEBD61B62EAB6746434EBC3CC315E32220B3BADD50BDC4C4E6C147FEDD43D",
+      "0680512BCBB42C07D47349D2153B70C4E5D7FDFCBFA36EA1A85841B9E46E09A2",
+      "8542D69E4C044F18E8B92435BF6FF7DD297720630485628D5AE74EE7C32E79B7",
+      "1",
+    ).unwrap()
+  }
+
+  /// 曲线方程是否由当前参数确定
+  pub(crate) fn judge_curve(&self, ec_curve: &ECCurveFp) -> bool {
+    ec_curve.q == self.p
+      && ec_curve.a.furnish_item_big_uint() == self.a
+      && ec_curve.b.furnish_item_big_uint() == self.b
+  }
+
+  /// 按GM/T 0003.1校验域参数：
+  /// 1. p为不超过256比特的奇素数；
+  /// 2. a、b、G的坐标在[0, p - 1]内，且$4a ^ 3 + 27b ^ 2 \not\equiv 0 \pmod p$；
+  /// 3. G在曲线上；
+  /// 4. n为素数，$n > 2 ^ {191}$且$n > 4 \sqrt p$，$[n]G = O$；
+  /// 5. $h = \lfloor (\sqrt p + 1) ^ 2 / n \rfloor$；
+  /// 6. 抗MOV攻击与异常曲线攻击：$n \neq p$，且$p ^ B \not\equiv 1 \pmod n$（$1 \le B \le 100$）；
+  /// 7. $h = 1$：点加所用的Renes–Costello–Batina公式只在素数阶曲线上完备，余因子大于1的曲线不受支持。
+  /// ## Returns
+  /// 参数合法则返回`Ok(())`，否则返回Sm2Error::InvalidCurveParams
+  pub fn validate(&self) -> Result<(), Sm2Error> {
+    let Self { p, a, b, gx, gy, n, h } = self;
+    let reap = |judge: bool| if judge { Ok(()) } else { Err(Sm2Error::InvalidCurveParams) };
+
+    reap(p.bits() <= 256 && *p > BigUint::from(3u8) && judge_probable_prime(p))?;
+    reap(a < p && b < p && gx < p && gy < p)?;
+    let discriminant = (BigUint::from(4u8) * a.modpow(&BigUint::from(3u8), p) + BigUint::from(27u8) * b * b) % p;
+    reap(!discriminant.is_zero())?;
+    reap((gy * gy) % p == (gx.modpow(&BigUint::from(3u8), p) + a * gx + b) % p)?;
+
+    reap(n.bits() > 191 && n * n > p * 16u8 && judge_probable_prime(n))?;
+    let ec_curve = ECCurveFp::new(p.clone(), a.clone(), b.clone());
+    let ec_gpoint = ProjectivePoint::from_affine(
+      ec_curve.clone(),
+      &ec_curve.furnish_ec_field_from_biguint(gx.clone()),
+      &ec_curve.furnish_ec_field_from_biguint(gy.clone()),
+    );
+    reap(ec_gpoint.mul_vartime(n).judge_infty())?;
+
+    // (sqrt(p) + 1) ^ 2 = p + 1 + 2sqrt(p)，而floor(2sqrt(p)) = floor(sqrt(4p))
+    let hasse_upper = p + 1u8 + (p * 4u8).sqrt();
+    reap(*h == hasse_upper / n)?;
+    reap(h.is_one())?;
+
+    reap(n != p)?;
+    let mut p_pow = BigUint::one();
+    for _ in 0..CURVE_MOV_THRESHOLD {
+      p_pow = p_pow * p % n;
+      reap(!p_pow.is_one())?;
+    }
+
+    Ok(())
+  }
+}
+
+
+/// Miller-Rabin素性检测，底数随机选取，合数通过检测的概率不超过$4 ^ {-32}$
+fn judge_probable_prime(dial: &BigUint) -> bool {
+  let two = BigUint::from(2u8);
+  if *dial < two {
+    return false;
+  }
+  for small_prime in [2u8, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37] {
+    if (dial % small_prime).is_zero() {
+      return *dial == BigUint::from(small_prime);
+    }
+  }
+
+  let dial_m1 = dial - 1u8;
+  let s = dial_m1.trailing_zeros().unwrap_or(0);
+  let d = &dial_m1 >> s;
+  let mut rng = rand::thread_rng();
+  'witness: for _ in 0..PRIME_TEST_ROUNDS {
+    let base = rng.gen_biguint_range(&two, &dial_m1);
+    let mut x = base.modpow(&d, dial);
+    if x.is_one() || x == dial_m1 {
+      continue;
+    }
+    for _ in 1..s {
+      x = &x * &x % dial;
+      if x == dial_m1 {
+        continue 'witness;
+      }
+    }
+    return false;
+  }
+  true
+}
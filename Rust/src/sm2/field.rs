@@ -0,0 +1,247 @@
+use num_bigint::BigUint;
+
+
+/// 4个u64组成的256比特整数，小端序（limbs[0]为最低位）
+pub type Limbs = [u64; 4];
+
+
+/// 带进位加法，返回`(a + b + carry) mod 2^64`和新的进位
+#[inline(always)]
+fn adc(a: u64, b: u64, carry: u64) -> (u64, u64) {
+  let t = a as u128 + b as u128 + carry as u128;
+  (t as u64, (t >> 64) as u64)
+}
+
+
+/// 带借位减法，返回`(a - b - borrow) mod 2^64`和新的借位（0或1）
+#[inline(always)]
+fn sbb(a: u64, b: u64, borrow: u64) -> (u64, u64) {
+  let t = (a as u128).wrapping_sub(b as u128 + borrow as u128);
+  (t as u64, (t >> 127) as u64)
+}
+
+
+/// 乘加，返回`(acc + a * b + carry)`的低64位和高64位
+#[inline(always)]
+fn mac(acc: u64, a: u64, b: u64, carry: u64) -> (u64, u64) {
+  let t = acc as u128 + a as u128 * b as u128 + carry as u128;
+  (t as u64, (t >> 64) as u64)
+}
+
+
+/// 按掩码选择，mask为全1时取b，为0时取a，不产生分支
+#[inline(always)]
+pub fn ct_select(a: &Limbs, b: &Limbs, mask: u64) -> Limbs {
+  [
+    a[0] ^ (mask & (a[0] ^ b[0])),
+    a[1] ^ (mask & (a[1] ^ b[1])),
+    a[2] ^ (mask & (a[2] ^ b[2])),
+    a[3] ^ (mask & (a[3] ^ b[3])),
+  ]
+}
+
+
+/// 相等时返回全1掩码，否则返回0，不产生分支
+#[inline(always)]
+pub fn ct_eq_mask(a: u64, b: u64) -> u64 {
+  let t = a ^ b;
+  ((t | t.wrapping_neg()) >> 63).wrapping_sub(1)
+}
+
+
+/// 按掩码交换，mask为全1时交换a与b，为0时保持不变，不产生分支
+#[inline(always)]
+pub fn ct_swap(a: &mut Limbs, b: &mut Limbs, mask: u64) {
+  for (ai, bi) in a.iter_mut().zip(b.iter_mut()) {
+    let t = mask & (*ai ^ *bi);
+    *ai ^= t;
+    *bi ^= t;
+  }
+}
+
+
+/// 大整数转limbs，要求小于2^256
+pub fn big_uint_to_limbs(dial: &BigUint) -> Limbs {
+  let mut limbs = [0u64; 4];
+  for (limbi, digit) in limbs.iter_mut().zip(dial.iter_u64_digits()) {
+    *limbi = digit;
+  }
+  limbs
+}
+
+
+/// limbs转大整数
+pub fn limbs_to_big_uint(limbs: &Limbs) -> BigUint {
+  let mut digits = Vec::with_capacity(8);
+  for limb in limbs {
+    digits.push(*limb as u32);
+    digits.push((*limb >> 32) as u32);
+  }
+  BigUint::new(digits)
+}
+
+
+/// 模数为不超过256比特的奇素数的有限域，元素以Montgomery形式$xR \mod p$（$R = 2^{256}$）保存；
+/// 加、减、乘均为固定的limb运算，不依赖元素的取值分支，也不分配内存
+/// - p: 模数
+/// - p_inv: $-p^{-1} \mod 2^{64}$
+/// - r2: $R^2 \mod p$，用于转入Montgomery形式
+/// - one: $R \mod p$，即Montgomery形式的1
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MontField {
+  p: Limbs,
+  p_inv: u64,
+  r2: Limbs,
+  one: Limbs,
+}
+
+
+impl MontField {
+  /// 构造有限域，不会对`p`是否是素数做检查
+  /// ## Parameters
+  /// - p: 奇数模数，不超过256比特
+  pub fn new(p: &BigUint) -> Self {
+    assert!(p.bit(0) && p.bits() <= 256, "modulus must be odd and at most 256 bits");
+
+    // 牛顿迭代求p^{-1} mod 2^64，每轮正确位数翻倍
+    let p0 = p.iter_u64_digits().next().unwrap_or(0);
+    let mut inv = 1u64;
+    for _ in 0..6 {
+      inv = inv.wrapping_mul(2u64.wrapping_sub(p0.wrapping_mul(inv)));
+    }
+
+    let r = BigUint::from(1u8) << 256u32;
+    Self {
+      p: big_uint_to_limbs(p),
+      p_inv: inv.wrapping_neg(),
+      r2: big_uint_to_limbs(&(&r * &r % p)),
+      one: big_uint_to_limbs(&(r % p)),
+    }
+  }
+
+  /// 模数
+  pub fn modulus(&self) -> BigUint {
+    limbs_to_big_uint(&self.p)
+  }
+
+  /// Montgomery形式的0
+  pub fn zero(&self) -> Limbs {
+    [0u64; 4]
+  }
+
+  /// Montgomery形式的1
+  pub fn one(&self) -> Limbs {
+    self.one
+  }
+
+  /// 大整数转入Montgomery形式，先对p取模
+  pub fn furnish_limbs(&self, dial: &BigUint) -> Limbs {
+    let dial = big_uint_to_limbs(&(dial % self.modulus()));
+    self.mul(&dial, &self.r2)
+  }
+
+  /// Montgomery形式转回大整数
+  pub fn furnish_big_uint(&self, a: &Limbs) -> BigUint {
+    limbs_to_big_uint(&self.mul(a, &[1, 0, 0, 0]))
+  }
+
+  /// 在`[0, 2p)`内的`hi * 2^256 + t`减去一次p
+  #[inline(always)]
+  fn reduce_once(&self, t: &Limbs, hi: u64) -> Limbs {
+    let (u0, borrow) = sbb(t[0], self.p[0], 0);
+    let (u1, borrow) = sbb(t[1], self.p[1], borrow);
+    let (u2, borrow) = sbb(t[2], self.p[2], borrow);
+    let (u3, borrow) = sbb(t[3], self.p[3], borrow);
+    // 有进位或无借位时，结果不小于p，取减去p后的值
+    let mask = 0u64.wrapping_sub(hi | (borrow ^ 1));
+    ct_select(t, &[u0, u1, u2, u3], mask)
+  }
+
+  /// 加法
+  pub fn add(&self, a: &Limbs, b: &Limbs) -> Limbs {
+    let (t0, carry) = adc(a[0], b[0], 0);
+    let (t1, carry) = adc(a[1], b[1], carry);
+    let (t2, carry) = adc(a[2], b[2], carry);
+    let (t3, carry) = adc(a[3], b[3], carry);
+    self.reduce_once(&[t0, t1, t2, t3], carry)
+  }
+
+  /// 减法
+  pub fn sub(&self, a: &Limbs, b: &Limbs) -> Limbs {
+    let (t0, borrow) = sbb(a[0], b[0], 0);
+    let (t1, borrow) = sbb(a[1], b[1], borrow);
+    let (t2, borrow) = sbb(a[2], b[2], borrow);
+    let (t3, borrow) = sbb(a[3], b[3], borrow);
+    // 有借位时加回p
+    let mask = 0u64.wrapping_sub(borrow);
+    let (u0, carry) = adc(t0, self.p[0] & mask, 0);
+    let (u1, carry) = adc(t1, self.p[1] & mask, carry);
+    let (u2, carry) = adc(t2, self.p[2] & mask, carry);
+    let (u3, _) = adc(t3, self.p[3] & mask, carry);
+    [u0, u1, u2, u3]
+  }
+
+  /// 取反
+  pub fn neg(&self, a: &Limbs) -> Limbs {
+    self.sub(&[0u64; 4], a)
+  }
+
+  /// 二倍
+  pub fn double(&self, a: &Limbs) -> Limbs {
+    self.add(a, a)
+  }
+
+  /// Montgomery乘法（CIOS），返回$abR^{-1} \mod p$
+  pub fn mul(&self, a: &Limbs, b: &Limbs) -> Limbs {
+    let p = &self.p;
+    let mut t = [0u64; 6];
+
+    for bi in b {
+      // t = t + a * b[i]
+      let mut carry = 0u64;
+      for j in 0..4 {
+        (t[j], carry) = mac(t[j], a[j], *bi, carry);
+      }
+      (t[4], carry) = adc(t[4], carry, 0);
+      t[5] = carry;
+
+      // t = (t + m * p) / 2^64，其中m使t的最低limb为0
+      let m = t[0].wrapping_mul(self.p_inv);
+      let (_, mut carry) = mac(t[0], m, p[0], 0);
+      for j in 1..4 {
+        (t[j - 1], carry) = mac(t[j], m, p[j], carry);
+      }
+      (t[3], carry) = adc(t[4], carry, 0);
+      t[4] = t[5] + carry;
+    }
+
+    self.reduce_once(&[t[0], t[1], t[2], t[3]], t[4])
+  }
+
+  /// 平方
+  pub fn sqr(&self, a: &Limbs) -> Limbs {
+    self.mul(a, a)
+  }
+
+  /// 幂运算，指数为公开值（如由p导出的求逆和开方指数），按其比特分支不泄露底数
+  pub fn pow(&self, a: &Limbs, exp: &BigUint) -> Limbs {
+    let mut reap = self.one;
+    for i in (0..exp.bits()).rev() {
+      reap = self.sqr(&reap);
+      if exp.bit(i) {
+        reap = self.mul(&reap, a);
+      }
+    }
+    reap
+  }
+
+  /// 由费马小定理求逆$a^{p - 2}$，0的逆记为0
+  pub fn inv(&self, a: &Limbs) -> Limbs {
+    self.pow(a, &(self.modulus() - 2u8))
+  }
+
+  /// 是否为0
+  pub fn is_zero(&self, a: &Limbs) -> bool {
+    (a[0] | a[1] | a[2] | a[3]) == 0
+  }
+}
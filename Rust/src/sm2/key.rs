@@ -0,0 +1,257 @@
+#![allow(deprecated)]
+
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use num_bigint::BigUint;
+use num_traits::{Num, Zero};
+use crate::secret::Secret;
+use crate::sm2::ec::{CurveParams, ProjectivePoint};
+use crate::sm2::sm2::{Sm2, Sm2Error};
+use crate::sm2::util::*;
+
+
+/// SM2公钥，构造时即按GM/T 0003完成校验，并缓存解析后的椭圆曲线点
+/// - point: 公钥对应的椭圆曲线点（仿射坐标）
+/// - x: 公钥的`x`坐标
+/// - y: 公钥的`y`坐标
+#[derive(Clone, Debug)]
+pub struct Sm2PublicKey {
+  pub(crate) point: ProjectivePoint,
+  pub(crate) x: BigUint,
+  pub(crate) y: BigUint,
+}
+
+
+impl Sm2PublicKey {
+  /// 由椭圆曲线点构造公钥，要求点已经过校验
+  fn from_point(point: ProjectivePoint) -> Result<Self, Sm2Error> {
+    let affine_point = point.to_affine();
+    let x = affine_point.furnish_x().ok_or(Sm2Error::EllipticCurveError)?.furnish_item_big_uint();
+    let y = affine_point.furnish_y().ok_or(Sm2Error::EllipticCurveError)?.furnish_item_big_uint();
+    Ok(Self { point, x, y })
+  }
+
+  /// 解析十六进制格式的公钥
+  /// ## Parameters
+  /// - sm2: SM2椭圆曲线参数
+  /// - public_key: 十六进制格式的公钥，支持`04`（130字符）和`02`/`03`（66字符）等前缀
+  pub fn from_hex(sm2: &Sm2, public_key: &str) -> Result<Self, Sm2Error> {
+    Self::from_point(sm2.furnish_valid_public_key_point(public_key)?)
+  }
+
+  /// 解析字节数组格式的公钥
+  /// ## Parameters
+  /// - sm2: SM2椭圆曲线参数
+  /// - public_key: 字节数组格式的公钥，支持`04`（65字节）和`02`/`03`（33字节）等前缀
+  pub fn from_bytes(sm2: &Sm2, public_key: &[u8]) -> Result<Self, Sm2Error> {
+    Self::from_hex(sm2, &arrs_to_hex(public_key))
+  }
+
+  /// 由私钥导出公钥
+  pub fn from_private_key(private_key: &Sm2PrivateKey) -> Self {
+    private_key.public_key.clone()
+  }
+
+  /// 公钥的`x`坐标，按坐标长度补0的十六进制字符串（sm2p256v1为64字符）
+  pub fn x_hex(&self) -> String {
+    hex_left_zero_pad(&self.x.to_str_radix(16), 2 * self.point.curve.furnish_coord_len())
+  }
+
+  /// 公钥的`y`坐标，按坐标长度补0的十六进制字符串（sm2p256v1为64字符）
+  pub fn y_hex(&self) -> String {
+    hex_left_zero_pad(&self.y.to_str_radix(16), 2 * self.point.curve.furnish_coord_len())
+  }
+
+  /// 是否为sm2p256v1上的公钥，PKCS#8、SubjectPublicKeyInfo等编码只适用于该曲线
+  pub(crate) fn judge_sm2p256v1(&self) -> bool {
+    CurveParams::sm2p256v1().judge_curve(&self.point.curve)
+  }
+
+  /// 是否为`sm2`所用曲线上的公钥
+  pub(crate) fn judge_curve(&self, sm2: &Sm2) -> bool {
+    self.point.curve == sm2.ec_curve
+  }
+
+  /// 130字符的十六进制格式的完整公钥
+  pub fn to_hex(&self) -> String {
+    format!("04{}{}", self.x_hex(), self.y_hex())
+  }
+
+  /// 66字符的十六进制格式的压缩公钥
+  pub fn to_compressed_hex(&self) -> String {
+    let compress_prefix = if self.y.bit(0) { "03" } else { "02" };
+    format!("{}{}", compress_prefix, self.x_hex())
+  }
+
+  /// 65字节的完整公钥
+  pub fn to_bytes(&self) -> Vec<u8> {
+    hex_anly_arrs(&self.to_hex())
+  }
+}
+
+
+impl PartialEq for Sm2PublicKey {
+  fn eq(&self, other: &Self) -> bool {
+    self.point.curve == other.point.curve && self.x == other.x && self.y == other.y
+  }
+}
+
+
+impl Eq for Sm2PublicKey {}
+
+
+/// 与PartialEq一致，不同曲线上坐标相同的公钥只会发生碰撞
+impl Hash for Sm2PublicKey {
+  fn hash<H: Hasher>(&self, state: &mut H) {
+    self.x.hash(state);
+    self.y.hash(state);
+  }
+}
+
+
+/// SM2私钥，构造时即校验$d \in [1, n - 2]$，并缓存对应的公钥
+/// - d: 私钥
+/// - public_key: 私钥对应的公钥$[d]G$
+#[derive(Clone)]
+pub struct Sm2PrivateKey {
+  pub(crate) d: Secret<BigUint>,
+  pub(crate) public_key: Sm2PublicKey,
+}
+
+
+impl Sm2PrivateKey {
+  /// 由大整数构造私钥
+  pub(crate) fn from_secret(sm2: &Sm2, d: Secret<BigUint>) -> Result<Self, Sm2Error> {
+    // (1 + d)须在模n下可逆，故d不能为n - 1
+    if d.is_zero() || *d >= &sm2.ec_n - 1u8 {
+      return Err(Sm2Error::InvalidPrivateKey);
+    }
+
+    let public_key = Sm2PublicKey::from_point(sm2.furnish_gpoint_mul(&d))?;
+    Ok(Self { d, public_key })
+  }
+
+  /// 解析十六进制格式的私钥
+  /// ## Parameters
+  /// - sm2: SM2椭圆曲线参数
+  /// - private_key: 十六进制格式的私钥，至多为坐标长度的两倍（sm2p256v1为64字符）
+  pub fn from_hex(sm2: &Sm2, private_key: &str) -> Result<Self, Sm2Error> {
+    if private_key.is_empty() || private_key.len() > 2 * sm2.furnish_coord_len() {
+      return Err(Sm2Error::InvalidPrivateKey);
+    }
+    let d = Secret(BigUint::from_str_radix(private_key, 16).map_err(|_| Sm2Error::InvalidPrivateKey)?);
+    Self::from_secret(sm2, d)
+  }
+
+  /// 解析字节数组格式的私钥
+  /// ## Parameters
+  /// - sm2: SM2椭圆曲线参数
+  /// - private_key: 与坐标等长的私钥（sm2p256v1为32字节）
+  pub fn from_bytes(sm2: &Sm2, private_key: &[u8]) -> Result<Self, Sm2Error> {
+    if private_key.len() != sm2.furnish_coord_len() {
+      return Err(Sm2Error::InvalidPrivateKey);
+    }
+    Self::from_secret(sm2, Secret(BigUint::from_bytes_be(private_key)))
+  }
+
+  /// 私钥对应的公钥
+  pub fn public_key(&self) -> &Sm2PublicKey {
+    &self.public_key
+  }
+
+  /// 是否为`sm2`所用曲线上的私钥
+  pub(crate) fn judge_curve(&self, sm2: &Sm2) -> bool {
+    self.public_key.judge_curve(sm2)
+  }
+
+  /// 64字符的十六进制格式的私钥，非sm2p256v1曲线按坐标长度补0
+  pub fn to_hex(&self) -> String {
+    hex_left_zero_pad(&self.d.to_str_radix(16), 2 * self.public_key.point.curve.furnish_coord_len())
+  }
+
+  /// 与坐标等长的私钥（sm2p256v1为32字节）
+  pub fn to_bytes(&self) -> Vec<u8> {
+    hex_anly_arrs(&self.to_hex())
+  }
+}
+
+
+impl fmt::Debug for Sm2PrivateKey {
+  /// 不输出私钥本身
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_struct("Sm2PrivateKey").field("public_key", &self.public_key).finish_non_exhaustive()
+  }
+}
+
+
+/// 私钥在释放时擦除
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for Sm2PrivateKey {}
+
+
+impl From<&Sm2PrivateKey> for Sm2PublicKey {
+  fn from(private_key: &Sm2PrivateKey) -> Self {
+    Self::from_private_key(private_key)
+  }
+}
+
+
+/// 将输入内容转化为SM2公钥
+pub trait ConvertSm2PublicKey {
+  fn convert_to_sm2_public_key(&self, sm2: &Sm2) -> Result<Sm2PublicKey, Sm2Error>;
+}
+
+
+/// 将输入内容转化为SM2私钥
+pub trait ConvertSm2PrivateKey {
+  fn convert_to_sm2_private_key(&self, sm2: &Sm2) -> Result<Sm2PrivateKey, Sm2Error>;
+}
+
+
+/// 对字符串实现ConvertSm2PublicKey和ConvertSm2PrivateKey；公钥支持十六进制和PEM格式的SubjectPublicKeyInfo
+macro_rules! impl_convert_sm2_key_about_str {
+  ($($t:ty),*) => {
+    $(
+      impl ConvertSm2PublicKey for $t {
+        fn convert_to_sm2_public_key(&self, sm2: &Sm2) -> Result<Sm2PublicKey, Sm2Error> {
+          if self.trim_start().starts_with("-----BEGIN") {
+            Sm2PublicKey::from_public_key_pem(sm2, self)
+          } else {
+            Sm2PublicKey::from_hex(sm2, self)
+          }
+        }
+      }
+
+      impl ConvertSm2PrivateKey for $t {
+        fn convert_to_sm2_private_key(&self, sm2: &Sm2) -> Result<Sm2PrivateKey, Sm2Error> {
+          Sm2PrivateKey::from_hex(sm2, self)
+        }
+      }
+    )*
+  };
+}
+
+
+/// 对SM2密钥本身实现ConvertSm2PublicKey或ConvertSm2PrivateKey；密钥须与`sm2`位于同一曲线上，
+/// 否则返回Sm2Error::UnsupportedAlgorithm
+macro_rules! impl_convert_sm2_key_about_key {
+  ($tr:ident, $fn:ident, $key:ty, $($t:ty),*) => {
+    $(
+      impl $tr for $t {
+        fn $fn(&self, sm2: &Sm2) -> Result<$key, Sm2Error> {
+          if !self.judge_curve(sm2) {
+            return Err(Sm2Error::UnsupportedAlgorithm);
+          }
+          Ok((*self).clone())
+        }
+      }
+    )*
+  };
+}
+
+impl_convert_sm2_key_about_str!(String, &String, &str);
+impl_convert_sm2_key_about_key!(
+  ConvertSm2PublicKey, convert_to_sm2_public_key, Sm2PublicKey, Sm2PublicKey, &Sm2PublicKey);
+impl_convert_sm2_key_about_key!(
+  ConvertSm2PrivateKey, convert_to_sm2_private_key, Sm2PrivateKey, Sm2PrivateKey, &Sm2PrivateKey);
@@ -1,7 +1,21 @@
 // mod asn1;
 mod ec;
+mod field;
+mod pkcs8;
+mod rfc6979;
+mod rfc9380;
+mod spki;
 pub mod util;
 #[deprecated(
   note = "SM2 implementation is for learning only, not production safe"
 )]
+pub mod key;
+#[allow(clippy::module_inception)]
+#[deprecated(
+  note = "SM2 implementation is for learning only, not production safe"
+)]
 pub mod sm2;
+#[deprecated(
+  note = "SM2 implementation is for learning only, not production safe"
+)]
+pub mod x509;
@@ -0,0 +1,383 @@
+#![allow(deprecated)]
+
+
+use der::asn1::{Any, BitString, ObjectIdentifier, OctetString};
+use der::pem::LineEnding;
+use der::{Decode, Encode, Sequence};
+use num_bigint::BigUint;
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore};
+use crate::secret::Secret;
+use crate::sm2::key::*;
+use crate::sm2::sm2::{Sm2, Sm2Error};
+use crate::sm3::achieve::sm3_pbkdf2;
+use crate::sm4::{Sm4, Sm4CryptoTrait, Sm4ModeKind, Sm4PaddingKind};
+
+
+/// id-ecPublicKey
+pub(crate) const OID_EC_PUBLIC_KEY: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.10045.2.1");
+/// sm2p256v1，SM2推荐曲线
+pub(crate) const OID_SM2P256V1: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.156.10197.1.301");
+/// PBES2
+const OID_PBES2: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.5.13");
+/// PBKDF2
+const OID_PBKDF2: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.5.12");
+/// hmacWithSM3
+const OID_HMAC_SM3: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.156.10197.1.401.3.1");
+/// SM4-CBC
+const OID_SM4_CBC: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.156.10197.1.104.2");
+
+/// 加密PKCS#8默认的PBKDF2迭代次数，与OpenSSL保持一致
+const PBKDF2_ITERATIONS: u32 = 2048;
+/// 解析加密PKCS#8时允许的最大PBKDF2迭代次数，防止恶意构造的迭代次数长时间占用CPU
+const PBKDF2_MAX_ITERATIONS: u32 = 10_000_000;
+const PBKDF2_SALT_LEN: usize = 16;
+const SM4_BLOCK: usize = 16;
+
+const PEM_LABEL_PKCS8: &str = "PRIVATE KEY";
+const PEM_LABEL_PKCS8_ENCRYPTED: &str = "ENCRYPTED PRIVATE KEY";
+const PEM_LABEL_SEC1: &str = "EC PRIVATE KEY";
+/// OpenSSL导出SM2私钥时使用的SEC1标签
+const PEM_LABEL_SEC1_SM2: &str = "SM2 PRIVATE KEY";
+
+
+/// AlgorithmIdentifier ::= SEQUENCE { algorithm OBJECT IDENTIFIER, parameters ANY OPTIONAL }
+#[derive(Clone, Debug, Eq, PartialEq, Sequence)]
+pub(crate) struct AlgorithmIdentifierDer {
+  pub algorithm: ObjectIdentifier,
+  pub parameters: Option<Any>,
+}
+
+
+impl AlgorithmIdentifierDer {
+  /// id-ecPublicKey，参数为sm2p256v1
+  pub(crate) fn sm2() -> Result<Self, Sm2Error> {
+    Ok(Self {
+      algorithm: OID_EC_PUBLIC_KEY,
+      parameters: Some(Any::encode_from(&OID_SM2P256V1).map_err(|_| Sm2Error::Asn1Error)?),
+    })
+  }
+
+  /// 校验算法是否为SM2，兼容直接以sm2p256v1作为算法标识的实现
+  pub(crate) fn check_sm2(&self) -> Result<(), Sm2Error> {
+    if self.algorithm == OID_SM2P256V1 {
+      return Ok(());
+    }
+    if self.algorithm != OID_EC_PUBLIC_KEY {
+      return Err(Sm2Error::UnsupportedAlgorithm);
+    }
+    let curve = self.parameters.as_ref().ok_or(Sm2Error::UnsupportedAlgorithm)?
+      .decode_as::<ObjectIdentifier>().map_err(|_| Sm2Error::UnsupportedAlgorithm)?;
+    if curve != OID_SM2P256V1 {
+      return Err(Sm2Error::UnsupportedAlgorithm);
+    }
+    Ok(())
+  }
+}
+
+
+/// SEC1 ECPrivateKey
+#[derive(Clone, Debug, Eq, PartialEq, Sequence)]
+struct EcPrivateKeyDer {
+  version: u8,
+  private_key: OctetString,
+  #[asn1(context_specific = "0", tag_mode = "EXPLICIT", optional = "true")]
+  parameters: Option<ObjectIdentifier>,
+  #[asn1(context_specific = "1", tag_mode = "EXPLICIT", optional = "true")]
+  public_key: Option<BitString>,
+}
+
+
+/// PKCS#8 PrivateKeyInfo，attributes只解析不使用
+#[derive(Clone, Debug, Eq, PartialEq, Sequence)]
+struct PrivateKeyInfoDer {
+  version: u8,
+  private_key_algorithm: AlgorithmIdentifierDer,
+  private_key: OctetString,
+  #[asn1(context_specific = "0", tag_mode = "IMPLICIT", optional = "true")]
+  attributes: Option<Any>,
+  #[asn1(context_specific = "1", tag_mode = "IMPLICIT", optional = "true")]
+  public_key: Option<BitString>,
+}
+
+
+/// PKCS#8 EncryptedPrivateKeyInfo
+#[derive(Clone, Debug, Eq, PartialEq, Sequence)]
+struct EncryptedPrivateKeyInfoDer {
+  encryption_algorithm: AlgorithmIdentifierDer,
+  encrypted_data: OctetString,
+}
+
+
+/// PBES2-params
+#[derive(Clone, Debug, Eq, PartialEq, Sequence)]
+struct Pbes2ParamsDer {
+  key_derivation_func: AlgorithmIdentifierDer,
+  encryption_scheme: AlgorithmIdentifierDer,
+}
+
+
+/// PBKDF2-params，prf缺省为hmacWithSHA1，此处不支持
+#[derive(Clone, Debug, Eq, PartialEq, Sequence)]
+struct Pbkdf2ParamsDer {
+  salt: OctetString,
+  iteration_count: u32,
+  key_length: Option<u16>,
+  prf: Option<AlgorithmIdentifierDer>,
+}
+
+
+/// 解析PEM，并校验标签
+pub(crate) fn pem_anly_der(pem_talks: &str, labels: &[&str]) -> Result<Secret<Vec<u8>>, Sm2Error> {
+  let (label, der_arrs) = der::pem::decode_vec(pem_talks.as_bytes()).map_err(|_| Sm2Error::Asn1Error)?;
+  let der_arrs = Secret(der_arrs);
+  if !labels.contains(&label) {
+    return Err(Sm2Error::Asn1Error);
+  }
+  Ok(der_arrs)
+}
+
+
+/// DER编码为PEM
+pub(crate) fn der_to_pem(label: &str, der_arrs: &[u8]) -> Result<String, Sm2Error> {
+  der::pem::encode_string(label, LineEnding::LF, der_arrs).map_err(|_| Sm2Error::Asn1Error)
+}
+
+
+impl Sm2PrivateKey {
+  /// 编码为SEC1 ECPrivateKey
+  fn to_ec_private_key_der(&self, with_parameters: bool) -> Result<EcPrivateKeyDer, Sm2Error> {
+    if !self.public_key.judge_sm2p256v1() {
+      return Err(Sm2Error::UnsupportedAlgorithm);
+    }
+    Ok(EcPrivateKeyDer {
+      version: 1,
+      private_key: OctetString::new(self.to_bytes()).map_err(|_| Sm2Error::Asn1Error)?,
+      parameters: if with_parameters { Some(OID_SM2P256V1) } else { None },
+      public_key: Some(BitString::from_bytes(&self.public_key.to_bytes()).map_err(|_| Sm2Error::Asn1Error)?),
+    })
+  }
+
+  /// 解析SEC1 ECPrivateKey，若携带公钥则校验其与私钥是否匹配
+  fn from_ec_private_key_der(sm2: &Sm2, ec_prv_k: &EcPrivateKeyDer) -> Result<Self, Sm2Error> {
+    if ec_prv_k.version != 1 {
+      return Err(Sm2Error::Asn1Error);
+    }
+    if !sm2.judge_sm2p256v1() || ec_prv_k.parameters.is_some_and(|curve| curve != OID_SM2P256V1) {
+      return Err(Sm2Error::UnsupportedAlgorithm);
+    }
+
+    let d_arrs = ec_prv_k.private_key.as_bytes();
+    if d_arrs.is_empty() || d_arrs.len() > 32 {
+      return Err(Sm2Error::InvalidPrivateKey);
+    }
+    let private_key = Self::from_secret(sm2, Secret(BigUint::from_bytes_be(d_arrs)))?;
+
+    if let Some(pub_k) = &ec_prv_k.public_key {
+      let pub_k_arrs = pub_k.as_bytes().ok_or(Sm2Error::Asn1Error)?;
+      if Sm2PublicKey::from_bytes(sm2, pub_k_arrs)? != private_key.public_key {
+        return Err(Sm2Error::InvalidPublicKey);
+      }
+    }
+    Ok(private_key)
+  }
+
+  /// 解析DER编码的SEC1 ECPrivateKey
+  /// ## Parameters
+  /// - sm2: SM2椭圆曲线参数
+  /// - der_arrs: DER编码的私钥
+  pub fn from_sec1_der(sm2: &Sm2, der_arrs: &[u8]) -> Result<Self, Sm2Error> {
+    let ec_prv_k = EcPrivateKeyDer::from_der(der_arrs).map_err(|_| Sm2Error::Asn1Error)?;
+    Self::from_ec_private_key_der(sm2, &ec_prv_k)
+  }
+
+  /// 编码为DER格式的SEC1 ECPrivateKey，携带曲线参数和公钥
+  pub fn to_sec1_der(&self) -> Result<Vec<u8>, Sm2Error> {
+    self.to_ec_private_key_der(true)?.to_der().map_err(|_| Sm2Error::Asn1Error)
+  }
+
+  /// 解析PEM编码的SEC1 ECPrivateKey，标签为`EC PRIVATE KEY`或`SM2 PRIVATE KEY`
+  /// ## Parameters
+  /// - sm2: SM2椭圆曲线参数
+  /// - pem_talks: PEM编码的私钥
+  pub fn from_sec1_pem(sm2: &Sm2, pem_talks: &str) -> Result<Self, Sm2Error> {
+    Self::from_sec1_der(sm2, &pem_anly_der(pem_talks, &[PEM_LABEL_SEC1, PEM_LABEL_SEC1_SM2])?)
+  }
+
+  /// 编码为PEM格式的SEC1 ECPrivateKey
+  pub fn to_sec1_pem(&self) -> Result<String, Sm2Error> {
+    der_to_pem(PEM_LABEL_SEC1, &Secret(self.to_sec1_der()?))
+  }
+
+  /// 解析DER编码的PKCS#8 PrivateKeyInfo
+  /// ## Parameters
+  /// - sm2: SM2椭圆曲线参数
+  /// - der_arrs: DER编码的私钥
+  pub fn from_pkcs8_der(sm2: &Sm2, der_arrs: &[u8]) -> Result<Self, Sm2Error> {
+    let prv_k_info = PrivateKeyInfoDer::from_der(der_arrs).map_err(|_| Sm2Error::Asn1Error)?;
+    if prv_k_info.version > 1 {
+      return Err(Sm2Error::Asn1Error);
+    }
+    prv_k_info.private_key_algorithm.check_sm2()?;
+
+    let ec_prv_k = EcPrivateKeyDer::from_der(prv_k_info.private_key.as_bytes())
+      .map_err(|_| Sm2Error::Asn1Error)?;
+    Self::from_ec_private_key_der(sm2, &ec_prv_k)
+  }
+
+  /// 编码为DER格式的PKCS#8 PrivateKeyInfo
+  pub fn to_pkcs8_der(&self) -> Result<Vec<u8>, Sm2Error> {
+    // 曲线参数已由privateKeyAlgorithm给出
+    let ec_prv_k = Secret(self.to_ec_private_key_der(false)?.to_der().map_err(|_| Sm2Error::Asn1Error)?);
+    PrivateKeyInfoDer {
+      version: 0,
+      private_key_algorithm: AlgorithmIdentifierDer::sm2()?,
+      private_key: OctetString::new(ec_prv_k.to_vec()).map_err(|_| Sm2Error::Asn1Error)?,
+      attributes: None,
+      public_key: None,
+    }.to_der().map_err(|_| Sm2Error::Asn1Error)
+  }
+
+  /// 解析PEM编码的PKCS#8 PrivateKeyInfo，标签为`PRIVATE KEY`
+  /// ## Parameters
+  /// - sm2: SM2椭圆曲线参数
+  /// - pem_talks: PEM编码的私钥
+  pub fn from_pkcs8_pem(sm2: &Sm2, pem_talks: &str) -> Result<Self, Sm2Error> {
+    Self::from_pkcs8_der(sm2, &pem_anly_der(pem_talks, &[PEM_LABEL_PKCS8])?)
+  }
+
+  /// 编码为PEM格式的PKCS#8 PrivateKeyInfo
+  pub fn to_pkcs8_pem(&self) -> Result<String, Sm2Error> {
+    der_to_pem(PEM_LABEL_PKCS8, &Secret(self.to_pkcs8_der()?))
+  }
+
+  /// 解析DER编码的加密PKCS#8 EncryptedPrivateKeyInfo，仅支持PBES2（PBKDF2 + HMAC-SM3，SM4-CBC）
+  /// ## Parameters
+  /// - sm2: SM2椭圆曲线参数
+  /// - der_arrs: DER编码的加密私钥
+  /// - password: 口令
+  /// ## Returns
+  /// 私钥；PBKDF2迭代次数超过10000000返回Sm2Error::InvalidData
+  pub fn from_pkcs8_encrypted_der(sm2: &Sm2, der_arrs: &[u8], password: &[u8]) -> Result<Self, Sm2Error> {
+    let enc_prv_k_info = EncryptedPrivateKeyInfoDer::from_der(der_arrs).map_err(|_| Sm2Error::Asn1Error)?;
+    let enc_alg = &enc_prv_k_info.encryption_algorithm;
+    if enc_alg.algorithm != OID_PBES2 {
+      return Err(Sm2Error::UnsupportedAlgorithm);
+    }
+    let pbes2 = enc_alg.parameters.as_ref().ok_or(Sm2Error::Asn1Error)?
+      .decode_as::<Pbes2ParamsDer>().map_err(|_| Sm2Error::Asn1Error)?;
+
+    // 口令派生密钥
+    if pbes2.key_derivation_func.algorithm != OID_PBKDF2 {
+      return Err(Sm2Error::UnsupportedAlgorithm);
+    }
+    let pbkdf2 = pbes2.key_derivation_func.parameters.as_ref().ok_or(Sm2Error::Asn1Error)?
+      .decode_as::<Pbkdf2ParamsDer>().map_err(|_| Sm2Error::Asn1Error)?;
+    if pbkdf2.prf.as_ref().is_none_or(|prf| prf.algorithm != OID_HMAC_SM3) {
+      return Err(Sm2Error::UnsupportedAlgorithm);
+    }
+    if pbkdf2.iteration_count == 0 || pbkdf2.key_length.is_some_and(|keyl| keyl as usize != SM4_BLOCK) {
+      return Err(Sm2Error::Asn1Error);
+    }
+    if pbkdf2.iteration_count > PBKDF2_MAX_ITERATIONS {
+      return Err(Sm2Error::InvalidData);
+    }
+    let sm4_key = Secret(sm3_pbkdf2(password, pbkdf2.salt.as_bytes(), pbkdf2.iteration_count, SM4_BLOCK));
+
+    // SM4-CBC解密
+    if pbes2.encryption_scheme.algorithm != OID_SM4_CBC {
+      return Err(Sm2Error::UnsupportedAlgorithm);
+    }
+    let iv = pbes2.encryption_scheme.parameters.as_ref().ok_or(Sm2Error::Asn1Error)?
+      .decode_as::<OctetString>().map_err(|_| Sm2Error::Asn1Error)?;
+    let prv_k_info = Secret(Sm4::new().decrypt(
+      enc_prv_k_info.encrypted_data.as_bytes(), &sm4_key[..], Sm4ModeKind::Cbc, Some(Sm4PaddingKind::Pkcs7),
+      Some(iv.as_bytes()),
+    ).map_err(|_| Sm2Error::DecryptionError)?);
+
+    // 口令错误时解密结果无法解析
+    Self::from_pkcs8_der(sm2, &prv_k_info).map_err(|err| match err {
+      | Sm2Error::Asn1Error => Sm2Error::DecryptionError,
+      | err => err,
+    })
+  }
+
+  /// 编码为DER格式的加密PKCS#8 EncryptedPrivateKeyInfo，使用PBES2（PBKDF2 + HMAC-SM3，SM4-CBC），
+  /// 盐值和IV由OsRng生成
+  /// ## Parameters
+  /// - password: 口令
+  pub fn to_pkcs8_encrypted_der(&self, password: &[u8]) -> Result<Vec<u8>, Sm2Error> {
+    self.to_pkcs8_encrypted_der_with_rng(password, &mut OsRng)
+  }
+
+  /// 编码为DER格式的加密PKCS#8，盐值和IV由指定的随机数发生器生成
+  /// ## Parameters
+  /// - password: 口令
+  /// - rng: 密码学安全的随机数发生器
+  pub fn to_pkcs8_encrypted_der_with_rng<R: CryptoRng + RngCore>(
+    &self, password: &[u8], rng: &mut R,
+  ) -> Result<Vec<u8>, Sm2Error> {
+    let mut salt = [0u8; PBKDF2_SALT_LEN];
+    let mut iv = [0u8; SM4_BLOCK];
+    rng.fill_bytes(&mut salt);
+    rng.fill_bytes(&mut iv);
+
+    let sm4_key = Secret(sm3_pbkdf2(password, &salt, PBKDF2_ITERATIONS, SM4_BLOCK));
+    let prv_k_info = Secret(self.to_pkcs8_der()?);
+    let encrypted_data = Sm4::new().encrypt(
+      &prv_k_info[..], &sm4_key[..], Sm4ModeKind::Cbc, Some(Sm4PaddingKind::Pkcs7), Some(&iv[..]),
+    ).map_err(|_| Sm2Error::EncryptionError)?;
+
+    let pbkdf2 = Pbkdf2ParamsDer {
+      salt: OctetString::new(salt).map_err(|_| Sm2Error::Asn1Error)?,
+      iteration_count: PBKDF2_ITERATIONS,
+      key_length: None,
+      prf: Some(AlgorithmIdentifierDer { algorithm: OID_HMAC_SM3, parameters: Some(Any::null()) }),
+    };
+    let pbes2 = Pbes2ParamsDer {
+      key_derivation_func: AlgorithmIdentifierDer {
+        algorithm: OID_PBKDF2,
+        parameters: Some(Any::encode_from(&pbkdf2).map_err(|_| Sm2Error::Asn1Error)?),
+      },
+      encryption_scheme: AlgorithmIdentifierDer {
+        algorithm: OID_SM4_CBC,
+        parameters: Some(Any::encode_from(&OctetString::new(iv).map_err(|_| Sm2Error::Asn1Error)?)
+          .map_err(|_| Sm2Error::Asn1Error)?),
+      },
+    };
+
+    EncryptedPrivateKeyInfoDer {
+      encryption_algorithm: AlgorithmIdentifierDer {
+        algorithm: OID_PBES2,
+        parameters: Some(Any::encode_from(&pbes2).map_err(|_| Sm2Error::Asn1Error)?),
+      },
+      encrypted_data: OctetString::new(encrypted_data).map_err(|_| Sm2Error::Asn1Error)?,
+    }.to_der().map_err(|_| Sm2Error::Asn1Error)
+  }
+
+  /// 解析PEM编码的加密PKCS#8，标签为`ENCRYPTED PRIVATE KEY`
+  /// ## Parameters
+  /// - sm2: SM2椭圆曲线参数
+  /// - pem_talks: PEM编码的加密私钥
+  /// - password: 口令
+  pub fn from_pkcs8_encrypted_pem(sm2: &Sm2, pem_talks: &str, password: &[u8]) -> Result<Self, Sm2Error> {
+    Self::from_pkcs8_encrypted_der(sm2, &pem_anly_der(pem_talks, &[PEM_LABEL_PKCS8_ENCRYPTED])?, password)
+  }
+
+  /// 编码为PEM格式的加密PKCS#8
+  /// ## Parameters
+  /// - password: 口令
+  pub fn to_pkcs8_encrypted_pem(&self, password: &[u8]) -> Result<String, Sm2Error> {
+    der_to_pem(PEM_LABEL_PKCS8_ENCRYPTED, &self.to_pkcs8_encrypted_der(password)?)
+  }
+
+  /// 编码为PEM格式的加密PKCS#8，盐值和IV由指定的随机数发生器生成
+  /// ## Parameters
+  /// - password: 口令
+  /// - rng: 密码学安全的随机数发生器
+  pub fn to_pkcs8_encrypted_pem_with_rng<R: CryptoRng + RngCore>(
+    &self, password: &[u8], rng: &mut R,
+  ) -> Result<String, Sm2Error> {
+    der_to_pem(PEM_LABEL_PKCS8_ENCRYPTED, &self.to_pkcs8_encrypted_der_with_rng(password, rng)?)
+  }
+}
@@ -0,0 +1,90 @@
+use num_bigint::BigUint;
+use num_traits::Zero;
+use crate::secret::Secret;
+use crate::sm2::util::big_uint_to_arrs;
+use crate::sm3::achieve::sm3_hmac;
+
+
+/// SM3的输出比特长度
+const SM3_OUTPUT_BITS: u64 = 256;
+
+
+/// 按RFC 6979第3.2节以HMAC-SM3派生签名随机数k
+///
+/// SM3的输出长度为256比特，n的比特长度qlen不超过256，bits2int只需取最左侧qlen比特；
+/// sm2p256v1的qlen同为256，无需截断。
+/// 候选值导致r = 0、r + k = n或s = 0时，按3.2节h.3继续生成下一个候选值。
+/// - hmac_k: HMAC密钥K
+/// - hmac_v: 中间值V
+/// - n: 椭圆曲线的阶
+/// - qlen: n的比特长度
+/// - started: 是否已经生成过候选值
+pub(crate) struct Sm3NonceGenerator {
+  hmac_k: Secret<Vec<u8>>,
+  hmac_v: Secret<Vec<u8>>,
+  n: BigUint,
+  qlen: u64,
+  started: bool,
+}
+
+
+impl Sm3NonceGenerator {
+  /// ## Parameters
+  /// - d: 私钥
+  /// - e: 待签名的杂凑值
+  /// - n: 椭圆曲线的阶
+  pub(crate) fn new(d: &BigUint, e: &BigUint, n: &BigUint) -> Self {
+    let qlen = n.bits();
+    let rlen = qlen.div_ceil(8) as usize;
+    let d_arrs = Secret(big_uint_to_arrs(d, rlen));
+    // bits2octets(h1) = int2octets(bits2int(h1) mod n)
+    let e_arrs = big_uint_to_arrs(&(bits2int(e, qlen) % n), rlen);
+
+    let mut nonce_generator = Self {
+      hmac_k: Secret(vec![0x00; 32]),
+      hmac_v: Secret(vec![0x01; 32]),
+      n: n.clone(),
+      qlen,
+      started: false,
+    };
+    // K = HMAC_K(V || 0x00 || int2octets(x) || bits2octets(h1))，V = HMAC_K(V)
+    // K = HMAC_K(V || 0x01 || int2octets(x) || bits2octets(h1))，V = HMAC_K(V)
+    for sep in [0x00u8, 0x01] {
+      nonce_generator.hmac_k = Secret(sm3_hmac(
+        &nonce_generator.hmac_k, &Secret([&nonce_generator.hmac_v[..], &[sep], &d_arrs, &e_arrs].concat()),
+      ));
+      nonce_generator.hmac_v = Secret(sm3_hmac(&nonce_generator.hmac_k, &nonce_generator.hmac_v));
+    }
+    nonce_generator
+  }
+
+  /// K = HMAC_K(V || 0x00)，V = HMAC_K(V)
+  fn reseed(&mut self) {
+    self.hmac_k = Secret(sm3_hmac(&self.hmac_k, &[&self.hmac_v[..], &[0x00]].concat()));
+    self.hmac_v = Secret(sm3_hmac(&self.hmac_k, &self.hmac_v));
+  }
+
+  /// 生成下一个位于[1, n - 1]内的候选值
+  pub(crate) fn next_nonce(&mut self) -> Secret<BigUint> {
+    if self.started {
+      self.reseed();
+    }
+    self.started = true;
+
+    loop {
+      self.hmac_v = Secret(sm3_hmac(&self.hmac_k, &self.hmac_v));
+      let hmac_v_dial = Secret(BigUint::from_bytes_be(&self.hmac_v));
+      let k = Secret(bits2int(&hmac_v_dial, self.qlen));
+      if !k.is_zero() && *k < self.n {
+        return k;
+      }
+      self.reseed();
+    }
+  }
+}
+
+
+/// bits2int：取256比特杂凑值最左侧的qlen比特
+fn bits2int(dial: &BigUint, qlen: u64) -> BigUint {
+  dial >> (SM3_OUTPUT_BITS.saturating_sub(qlen) as usize)
+}
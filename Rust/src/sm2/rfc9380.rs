@@ -0,0 +1,112 @@
+#![allow(deprecated)]
+
+
+use std::sync::Arc;
+use num_bigint::BigUint;
+use crate::secret::Secret;
+use crate::sm2::ec::{ECCurveFp, ECFieldElementFp, ProjectivePoint};
+use crate::sm2::sm2::Sm2Error;
+use crate::sm3::achieve::sm3_expand_message_xmd;
+
+
+/// sm2p256v1上hash_to_curve的套件标识，输出与随机预言不可区分
+pub const SM2_H2C_SUITE_RO: &str = "SM2P256V1_XMD:SM3_SSWU_RO_";
+/// sm2p256v1上encode_to_curve的套件标识，输出不是均匀分布的
+pub const SM2_H2C_SUITE_NU: &str = "SM2P256V1_XMD:SM3_SSWU_NU_";
+
+
+/// hash_to_field中每个域元素的字节数$L = \lceil (\lceil \log_2 p \rceil + k) / 8 \rceil$，安全强度k = 128
+const H2C_FIELD_LEN: usize = 48;
+/// simplified SWU映射的参数$Z = -9$，按RFC 9380附录H.2对sm2p256v1求得；$\sqrt{-Z} = 3$
+const SSWU_MINUS_Z: u8 = 9;
+const SSWU_SQRT_MINUS_Z: u8 = 3;
+
+
+/// 按RFC 9380第5.2节将消息杂凑为count个域元素，expand_message_xmd使用SM3
+/// ## Returns
+/// 域元素；域分隔标签为空返回Sm2Error::InvalidDst
+fn hash_to_field(
+  ec_curve: &ECCurveFp, msg: &[u8], dst: &[u8], count: usize,
+) -> Result<Vec<ECFieldElementFp>, Sm2Error> {
+  let uniform_arrs = Secret(
+    sm3_expand_message_xmd(msg, dst, count * H2C_FIELD_LEN).map_err(|_| Sm2Error::InvalidDst)?
+  );
+  Ok(uniform_arrs.chunks(H2C_FIELD_LEN)
+    .map(|chunk| ec_curve.furnish_ec_field_from_biguint(BigUint::from_bytes_be(chunk)))
+    .collect())
+}
+
+
+/// $p \equiv 3 \mod 4$时的sqrt_ratio（RFC 9380附录F.2.1.2）：u / v为二次剩余时返回(true, $\sqrt{u / v}$)，
+/// 否则返回(false, $\sqrt{Z u / v}$)，只做固定指数的幂运算和掩码选择
+fn sqrt_ratio_3mod4(
+  u: &ECFieldElementFp, v: &ECFieldElementFp, c1: &BigUint, c2: &ECFieldElementFp,
+) -> (bool, ECFieldElementFp) {
+  let tv1 = v.sqr();
+  let tv2 = u.mul(v);
+  let tv1 = tv1.mul(&tv2);
+  let y1 = tv1.modpow(c1).mul(&tv2);
+  let y2 = y1.mul(c2);
+  let tv3 = y1.sqr().mul(v);
+  let judge_qr = tv3.ct_eq(u);
+  (judge_qr, y2.conditional_select(&y1, judge_qr))
+}
+
+
+/// simplified SWU映射（RFC 9380第6.6.2节，按附录F.2的无分支写法），要求$a \neq 0$、$b \neq 0$且$p \equiv 3 \mod 4$；
+/// 运算次数与u的取值无关，u可由口令等秘密导出
+fn map_to_curve_sswu(ec_curve: &Arc<ECCurveFp>, u: &ECFieldElementFp) -> ProjectivePoint {
+  let (a, b) = (&ec_curve.a, &ec_curve.b);
+  let one = ec_curve.furnish_ec_field_from_biguint(BigUint::from(1u8));
+  let z = ec_curve.furnish_ec_field_from_biguint(BigUint::from(SSWU_MINUS_Z)).negate();
+  let c1 = (&ec_curve.q - 3u8) >> 2u32;
+  let c2 = ec_curve.furnish_ec_field_from_biguint(BigUint::from(SSWU_SQRT_MINUS_Z));
+
+  // tv1 = Z * u ^ 2，tv2 = tv1 ^ 2 + tv1
+  let tv1 = z.mul(&u.sqr());
+  let tv2 = tv1.sqr().add(&tv1);
+  // 分子tv3 = B * (tv2 + 1)，分母tv4 = A * CMOV(Z, -tv2, tv2 != 0)
+  let tv3 = b.mul(&tv2.add(&one));
+  let tv4 = a.mul(&z.conditional_select(&tv2.negate(), !tv2.judge_zero()));
+  // g(x1)的分子tv2 = (tv3 ^ 2 + A * tv4 ^ 2) * tv3 + B * tv4 ^ 3，分母tv6 = tv4 ^ 3
+  let tv6 = tv4.sqr();
+  let tv2 = tv3.sqr().add(&a.mul(&tv6)).mul(&tv3);
+  let tv6 = tv6.mul(&tv4);
+  let tv2 = tv2.add(&b.mul(&tv6));
+
+  let (judge_gx1_square, y1) = sqrt_ratio_3mod4(&tv2, &tv6, &c1, &c2);
+  // g(x1)不是二次剩余时取$x_2 = Z u ^ 2 x_1$，$y_2 = \sqrt{g(x_2)} = Z u ^ 3 \cdot y_1$
+  let x = tv1.mul(&tv3).conditional_select(&tv3, judge_gx1_square);
+  let y = tv1.mul(u).mul(&y1).conditional_select(&y1, judge_gx1_square);
+  // y与u的sgn0一致
+  let y = y.negate().conditional_select(&y, u.sgn0() == y.sgn0());
+  let x = x.dvd(&tv4);
+
+  ProjectivePoint::from_affine(ec_curve.clone(), &x, &y)
+}
+
+
+/// hash_to_curve（RFC 9380第3节）：两个域元素分别映射后相加，sm2p256v1的余因子为1，无需清除
+/// ## Parameters
+/// - ec_curve: sm2p256v1曲线
+/// - msg: 消息
+/// - dst: 域分隔标签
+pub(crate) fn hash_to_curve(
+  ec_curve: &Arc<ECCurveFp>, msg: &[u8], dst: &[u8],
+) -> Result<ProjectivePoint, Sm2Error> {
+  let u = hash_to_field(ec_curve, msg, dst, 2)?;
+  Ok(map_to_curve_sswu(ec_curve, &u[0]).add(&map_to_curve_sswu(ec_curve, &u[1])))
+}
+
+
+/// encode_to_curve（RFC 9380第3节）：单个域元素映射，只能得到约一半的曲线点
+/// ## Parameters
+/// - ec_curve: sm2p256v1曲线
+/// - msg: 消息
+/// - dst: 域分隔标签
+pub(crate) fn encode_to_curve(
+  ec_curve: &Arc<ECCurveFp>, msg: &[u8], dst: &[u8],
+) -> Result<ProjectivePoint, Sm2Error> {
+  let u = hash_to_field(ec_curve, msg, dst, 1)?;
+  Ok(map_to_curve_sswu(ec_curve, &u[0]))
+}
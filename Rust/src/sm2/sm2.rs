@@ -1,11 +1,21 @@
 #![allow(deprecated)]
 
 
-use std::rc::Rc;
-use num_bigint::{BigInt, BigUint, RandBigInt, Sign};
-use num_traits::{Num, One, Zero};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::io;
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, PoisonError};
+use num_bigint::BigUint;
+use num_traits::{Num, Zero};
 use rand::rngs::OsRng;
-use crate::sm2::ec::{ECCurveFp, ECPointFp};
+use rand::{CryptoRng, RngCore};
+use crate::secret::Secret;
+pub use crate::sm2::ec::CurveParams;
+use crate::sm2::ec::{ECCurveFp, ECPointTable, ProjectivePoint};
+use crate::sm2::key::*;
+use crate::sm2::rfc6979::Sm3NonceGenerator;
+use crate::sm2::rfc9380;
+pub use crate::sm2::rfc9380::{SM2_H2C_SUITE_NU, SM2_H2C_SUITE_RO};
 use crate::sm2::util::*;
 use crate::sm3::achieve::*;
 
@@ -26,6 +36,32 @@ pub enum Sm2Error {
   DecryptionError,
   // 椭圆曲线错误
   EllipticCurveError,
+  // 公钥长度错误
+  InvalidPublicKeyLength,
+  // 公钥为无穷远点
+  PointAtInfinity,
+  // 坐标不在有限域[0, p - 1]内
+  CoordinateOutOfRange,
+  // 点不在椭圆曲线上
+  PointNotOnCurve,
+  // 点的阶不为n
+  InvalidPointOrder,
+  // ASN.1/DER或PEM编解码错误
+  Asn1Error,
+  // 不支持的算法或曲线
+  UnsupportedAlgorithm,
+  // 证书不合规或签名无效
+  InvalidCertificate,
+  // 证书不在有效期内
+  CertificateExpired,
+  // 无法构建到受信任根证书的证书链
+  UntrustedCertificate,
+  // 用户ID过长，超过8191字节
+  InvalidUserId,
+  // 椭圆曲线域参数不合法
+  InvalidCurveParams,
+  // hash-to-curve的域分隔标签为空
+  InvalidDst,
   Other(String),
 }
 
@@ -37,100 +73,238 @@ pub enum Sm2ModeKind {
 }
 
 
+/// 签名随机数k的生成方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sm2NonceKind {
+  // 由随机数发生器生成
+  Random,
+  // 按RFC 6979以HMAC-SM3由私钥和待签名杂凑值确定性派生，相同输入得到相同签名
+  Deterministic,
+}
+
+
+/// 十六进制字符串格式的密钥对
+///
+/// 私钥以普通字符串保存，可直接移出，移出后由调用方负责擦除；启用`zeroize`特性时可调用`zeroize()`擦除。
+/// 需要在释放时自动擦除请使用Sm2PrivateKey。
 pub struct Sm2KeyPair {
   pub private_key: String,
   pub public_key: String,
 }
 
 
+/// 只擦除私钥
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for Sm2KeyPair {
+  fn zeroize(&mut self) {
+    self.private_key.zeroize();
+  }
+}
+
+
+/// 缺省的用户ID
+const SM2_DEFAULT_USER_ID: &[u8] = b"1234567812345678";
+
+
+/// 用户ID的最大字节数，ENTL为两字节的比特长度
+pub const SM2_MAX_USER_ID_LEN: usize = 8191;
+
+
+/// sm2p256v1基点G的预计算窗口表，首次使用时生成，此后该曲线上的所有`Sm2`实例共享
+static SM2_GPOINT_TABLE: OnceLock<Arc<ECPointTable>> = OnceLock::new();
+/// sm2p256v1基点G的奇数倍点表，供批量验签计算$[s]G + [t]P_A$，同样在首次使用时生成并共享
+static SM2_GPOINT_ODD_MULTIPLES: OnceLock<Arc<Vec<ProjectivePoint>>> = OnceLock::new();
+
+
+/// 签名所用的随机点$(x_1, y_1) = [k]G$
 struct Sm2RandomPoint {
-  #[allow(dead_code)]
-  pub key_pair: Sm2KeyPair,
-  pub k: BigUint,
+  pub k: Secret<BigUint>,
   pub x1: BigUint,
 }
 
 
-/// Sm2椭圆曲线参数
+/// Sm2椭圆曲线参数，所有运算只需`&self`，可放入`Arc`在多线程间共享
 /// - ec_curve: 椭圆曲线
 /// - ec_gpoint: 椭圆曲线基点
 /// - ec_n: 椭圆曲线基点阶
+/// - curve_params: 构造时使用的椭圆曲线域参数
+/// - gpoint_table: 基点G的预计算窗口表，首次使用时生成
+/// - gpoint_odd_multiples: 基点G的奇数倍点表，首次批量验签时生成
+/// - rng: 密钥生成、加密和签名所用的随机数发生器，默认为OsRng，由互斥锁保护
 pub struct Sm2 {
-  pub ec_curve: Rc<ECCurveFp>,
-  pub ec_gpoint: ECPointFp,
+  pub ec_curve: Arc<ECCurveFp>,
+  pub ec_gpoint: ProjectivePoint,
   pub ec_n: BigUint,
+  curve_params: CurveParams,
+  gpoint_table: OnceLock<Arc<ECPointTable>>,
+  gpoint_odd_multiples: OnceLock<Arc<Vec<ProjectivePoint>>>,
+  rng: Mutex<Box<dyn RngCore + Send>>,
+}
+
+
+impl Default for Sm2 {
+  fn default() -> Self {
+    Self::new()
+  }
 }
 
 
 impl Sm2 {
+  /// 基于SM2推荐曲线sm2p256v1
   pub fn new() -> Self {
-    let cv_q = BigUint::from_str_radix(
-      "FFFFFFFEFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF00000000FFFFFFFFFFFFFFFF", 16).unwrap();
-    let cv_a = BigUint::from_str_radix(
-      "FFFFFFFEFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF00000000FFFFFFFFFFFFFFFC", 16).unwrap();
-    let cv_b = BigUint::from_str_radix(
-      "28E9FA9E9D9F5E344D5A9E4BCF6509A7F39789F515AB8F92DDBCBD414D940E93", 16).unwrap();
-    // 椭圆曲线
-    let ec_curve = ECCurveFp::new(cv_q, cv_a, cv_b);
+    Self::from_curve_params(CurveParams::sm2p256v1())
+  }
 
-    // 基点
-    let ec_g_x = "32C4AE2C1F1981195F9904466A39C9948FE30BBFF2660BE1715A4589334C74C7";
-    let ec_g_y = "BC3736A2F4F6779C59BDCEE36B692153D0A9877CC62A474002DF32E52139F0A0";
-    let ec_gpoint = ec_curve.decode_point_hex(&format!("04{}{}", ec_g_x, ec_g_y)).unwrap();
+  /// 基于自定义的椭圆曲线域参数，如GM/T 0003示例所用的曲线或测试用的曲线；
+  /// PKCS#8、SubjectPublicKeyInfo与X.509编码只适用于sm2p256v1
+  /// ## Parameters
+  /// - curve_params: 椭圆曲线域参数，构造前按GM/T 0003.1校验
+  /// ## Returns
+  /// 参数不合法则返回Sm2Error::InvalidCurveParams
+  pub fn with_curve(curve_params: CurveParams) -> Result<Self, Sm2Error> {
+    curve_params.validate()?;
+    Ok(Self::from_curve_params(curve_params))
+  }
 
+  /// 由已知合法的域参数构造
+  fn from_curve_params(curve_params: CurveParams) -> Self {
+    // 椭圆曲线
+    let ec_curve = ECCurveFp::new(curve_params.p.clone(), curve_params.a.clone(), curve_params.b.clone());
+    // 基点
+    let ec_gpoint = ProjectivePoint::from_affine(
+      ec_curve.clone(),
+      &ec_curve.furnish_ec_field_from_biguint(curve_params.gx.clone()),
+      &ec_curve.furnish_ec_field_from_biguint(curve_params.gy.clone()),
+    );
     // 基点阶
-    let ec_n = BigUint::from_str_radix(
-      "FFFFFFFEFFFFFFFFFFFFFFFFFFFFFFFF7203DF6B21C6052B53BBF40939D54123", 16).unwrap();
+    let ec_n = curve_params.n.clone();
 
     Self {
       ec_curve,
       ec_gpoint,
       ec_n,
+      curve_params,
+      gpoint_table: OnceLock::new(),
+      gpoint_odd_multiples: OnceLock::new(),
+      rng: Mutex::new(Box::new(OsRng)),
     }
   }
 
+  /// 椭圆曲线域参数
+  pub fn curve_params(&self) -> &CurveParams {
+    &self.curve_params
+  }
+
+  /// 是否基于sm2p256v1，PKCS#8、SubjectPublicKeyInfo等编码只适用于该曲线
+  pub(crate) fn judge_sm2p256v1(&self) -> bool {
+    self.curve_params == CurveParams::sm2p256v1()
+  }
+
+  /// 坐标的字节数，sm2p256v1为32
+  pub(crate) fn furnish_coord_len(&self) -> usize {
+    self.ec_curve.furnish_coord_len()
+  }
+
+  /// 按坐标长度左侧补0的十六进制字符串
+  fn furnish_coord_hex(&self, dial: &BigUint) -> String {
+    hex_left_zero_pad(&dial.to_str_radix(16), 2 * self.furnish_coord_len())
+  }
+
+  /// 使用指定的随机数发生器，如可复现的测试用发生器或经认证的DRBG
+  /// ## Parameters
+  /// - rng: 密码学安全的随机数发生器
+  pub fn with_rng<R: CryptoRng + RngCore + Send + 'static>(rng: R) -> Self {
+    let mut sm2 = Self::new();
+    sm2.set_rng(rng);
+    sm2
+  }
+
+  /// 替换随机数发生器
+  /// ## Parameters
+  /// - rng: 密码学安全的随机数发生器
+  pub fn set_rng<R: CryptoRng + RngCore + Send + 'static>(&mut self, rng: R) {
+    self.rng = Mutex::new(Box::new(rng));
+  }
+
+  /// 获取随机数发生器；其他线程持锁时panic不影响发生器状态，直接取回
+  fn lock_rng(&self) -> MutexGuard<'_, Box<dyn RngCore + Send>> {
+    self.rng.lock().unwrap_or_else(PoisonError::into_inner)
+  }
+
+  /// 以随机数发生器填充字节数组
+  pub(crate) fn fill_random_bytes(&self, arrs: &mut [u8]) {
+    self.lock_rng().fill_bytes(arrs);
+  }
+
   #[inline]
-  fn crypto_message_digest_xor(messages: &mut Vec<u8>, x2: Vec<u8>, y2: Vec<u8>) {
-    let z = [x2, y2].concat();
+  fn crypto_message_digest_xor(messages: &mut [u8], x2: &[u8], y2: &[u8]) {
     let mut cnt: u32 = 1;
     let mut gogga_cnt = 0;
-    let mut t = sm3_digest(&[z.to_vec(), cnt.to_be_bytes().to_vec()].concat());
+    let mut t = Secret(sm3_digest(&Secret([x2, y2, &cnt.to_be_bytes()].concat())));
     cnt += 1;
 
     for messagei in messages.iter_mut() {
       if gogga_cnt == t.len() {
-        t = sm3_digest(&[z.to_vec(), cnt.to_be_bytes().to_vec()].concat());
+        t = Secret(sm3_digest(&Secret([x2, y2, &cnt.to_be_bytes()].concat())));
         cnt += 1;
         gogga_cnt = 0;
       }
-      *messagei ^= t[gogga_cnt] & 0xff;
+      *messagei ^= t[gogga_cnt];
       gogga_cnt += 1;
     }
   }
 
+  /// 共享点$(x_2, y_2)$的字节数组$x_2 \| y_2$
+  fn furnish_shared_point_arrs(&self, rad_point: &ProjectivePoint) -> Result<Secret<Vec<u8>>, Sm2Error> {
+    let coordl = self.furnish_coord_len();
+    let rad_point = rad_point.to_affine();
+    let x2 = Secret(rad_point.furnish_x().ok_or(Sm2Error::EllipticCurveError)?.furnish_item_big_uint());
+    let y2 = Secret(rad_point.furnish_y().ok_or(Sm2Error::EllipticCurveError)?.furnish_item_big_uint());
+    Ok(Secret([big_uint_to_arrs(&x2, coordl), big_uint_to_arrs(&y2, coordl)].concat()))
+  }
+
+  /// 生成`[1, upper]`内的随机数：每次读取与upper等长的字节（sm2p256v1为32字节）作为大端整数，
+  /// 不在范围内则重新读取，因此随机数发生器输出的首个合法值即为结果
+  fn generate_scalar(&self, upper: &BigUint) -> Secret<BigUint> {
+    Self::generate_scalar_from(&mut **self.lock_rng(), upper)
+  }
+
+  /// 以指定的随机数发生器生成`[1, upper]`内的随机数
+  fn generate_scalar_from(rng: &mut dyn RngCore, upper: &BigUint) -> Secret<BigUint> {
+    let mut salty_arrs = Secret(vec![0u8; (upper.bits() as usize).div_ceil(8)]);
+    loop {
+      rng.fill_bytes(&mut salty_arrs);
+      let salty_seed = Secret(BigUint::from_bytes_be(&salty_arrs));
+      if !salty_seed.is_zero() && *salty_seed <= *upper {
+        return salty_seed;
+      }
+    }
+  }
+
   /// 生成十六进制字符串格式的私钥和公钥
   /// ## Parameters
   /// - seed: 可选的种子，用于生成密钥对，格式为`(种子字符串, 进制)`
   pub fn generate_key_pair_hex(&self, seed: Option<(String, u32)>) -> Result<Sm2KeyPair, Sm2Error> {
-    let ec_n_m1 = &self.ec_n - 1u8;
+    // 私钥取值范围为[1, n - 2]，与Sm2PrivateKey一致
+    let ec_n_m2 = &self.ec_n - 2u8;
     let salty_seed = match seed {
       Some((dial_talk, dial_base)) => {
-        let dial = BigUint::from_str_radix(&dial_talk, dial_base)
-          .map_err(|_| Sm2Error::InvalidPrivateKey)?;
-        dial % &ec_n_m1 + BigUint::one()
+        let dial = Secret(BigUint::from_str_radix(&dial_talk, dial_base)
+          .map_err(|_| Sm2Error::InvalidPrivateKey)?);
+        let mut salty_seed = Secret(&*dial % &ec_n_m2);
+        *salty_seed += 1u8;
+        salty_seed
       }
-      None => { OsRng.gen_biguint_below(&ec_n_m1) + BigUint::one() }
+      None => self.generate_scalar(&ec_n_m2),
     };
     // 私钥
-    let private_key = hex_left_zero_pad(&salty_seed.to_str_radix(16), 64);
-
-    let mut ec_point_salt = self.ec_gpoint.mul(&salty_seed);
-    let ec_point_salt_x = hex_left_zero_pad(
-      &ec_point_salt.furnish_x().ok_or(Sm2Error::EllipticCurveError)?
-        .furnish_item_big_uint().to_str_radix(16), 64);
-    let ec_point_salt_y = hex_left_zero_pad(
-      &ec_point_salt.furnish_y().ok_or(Sm2Error::EllipticCurveError)?
-        .furnish_item_big_uint().to_str_radix(16), 64);
+    let private_key = self.furnish_coord_hex(&salty_seed);
+
+    let ec_point_salt = self.furnish_gpoint_mul(&salty_seed).to_affine();
+    let ec_point_salt_x = self.furnish_coord_hex(
+      &ec_point_salt.furnish_x().ok_or(Sm2Error::EllipticCurveError)?.furnish_item_big_uint());
+    let ec_point_salt_y = self.furnish_coord_hex(
+      &ec_point_salt.furnish_y().ok_or(Sm2Error::EllipticCurveError)?.furnish_item_big_uint());
     // 公钥
     let public_key = format!("04{}{}", ec_point_salt_x, ec_point_salt_y);
 
@@ -140,140 +314,259 @@ impl Sm2 {
     })
   }
 
-  /// 压缩公钥
+  /// 生成SM2私钥，其中缓存了对应的公钥
+  pub fn generate_private_key(&self) -> Result<Sm2PrivateKey, Sm2Error> {
+    // 私钥取值范围为[1, n - 2]
+    Sm2PrivateKey::from_secret(self, self.generate_scalar(&(&self.ec_n - 2u8)))
+  }
+
+  /// 压缩公钥，不依赖曲线参数，因而只检查编码，不检查点是否在曲线上
   /// ## Parameters
-  /// - public_key: 130字符的十六进制格式的完整公钥
+  /// - public_key: 十六进制格式的完整公钥，支持`04`和`06`/`07`前缀（sm2p256v1为130字符）
   /// ## Returns
-  /// 66字符的压缩后的公钥
+  /// 压缩后的公钥（sm2p256v1为66字符）；非十六进制返回Sm2Error::CodingError，长度错误返回
+  /// Sm2Error::InvalidPublicKeyLength，前缀未知或与y的奇偶性不一致返回Sm2Error::InvalidPublicKey
   pub fn compress_public_key_hex(public_key: &str) -> Result<String, Sm2Error> {
-    if public_key.len() != 130 {
-      return Err(Sm2Error::InvalidPublicKey);
+    if !public_key.bytes().all(|byt| byt.is_ascii_hexdigit()) {
+      return Err(Sm2Error::CodingError);
+    }
+    if public_key.len() < 6 || public_key.len() % 4 != 2 {
+      return Err(Sm2Error::InvalidPublicKeyLength);
     }
 
     let pub_k_pl = (public_key.len() - 2) / 2;
     let pub_k_px = &public_key[2..2 + pub_k_pl];
     let pub_k_py = BigUint::from_str_radix(&public_key[2 + pub_k_pl..], 16)
-      .map_err(|_| Sm2Error::InvalidPrivateKey)?;
+      .map_err(|_| Sm2Error::CodingError)?;
 
     let compress_prefix = if &pub_k_py % 2u32 == BigUint::zero() { "02" } else { "03" };
+    match &public_key[..2] {
+      | "04" => {}
+      | "06" if compress_prefix == "02" => {}
+      | "07" if compress_prefix == "03" => {}
+      | _ => return Err(Sm2Error::InvalidPublicKey),
+    }
 
     Ok(format!("{}{}", compress_prefix, pub_k_px))
   }
 
-  /// 验证公钥是否为椭圆曲线上的点
+  /// 解压缩公钥，由x和前缀中y的奇偶性按曲线方程求出y，并按GM/T 0003校验公钥
   /// ## Parameters
-  /// - public_key: 公钥
-  pub fn verify_public_key(&self, public_key: &str) -> Result<bool, Sm2Error> {
-    let ec_curve = &self.ec_curve;
-    let mut pub_k_ec_point = ec_curve.decode_point_hex(public_key).unwrap();
-    let x = pub_k_ec_point.furnish_x().ok_or(Sm2Error::EllipticCurveError)?;
-    let y = pub_k_ec_point.furnish_y().ok_or(Sm2Error::EllipticCurveError)?;
-
-    Ok(y.sqr() == x.sqr().mul(&x).add(&x.mul(&ec_curve.a)).add(&ec_curve.b))
+  /// - public_key: 十六进制格式的公钥，通常为`02`/`03`前缀的压缩公钥（sm2p256v1为66字符），
+  ///   也接受`04`和`06`/`07`前缀
+  /// ## Returns
+  /// `04`前缀的完整公钥（sm2p256v1为130字符）；x不是任何曲线点的横坐标时返回Sm2Error::PointNotOnCurve，
+  /// 其余同`verify_public_key`
+  pub fn decompress_public_key_hex(&self, public_key: &str) -> Result<String, Sm2Error> {
+    Ok(Sm2PublicKey::from_hex(self, public_key)?.to_hex())
   }
 
+  /// 按GM/T 0003校验公钥
+  /// ## Parameters
+  /// - public_key: 十六进制格式的公钥，支持`04`（130字符）和`02`/`03`（66字符）等前缀
+  /// ## Returns
+  /// 公钥合法则返回`Ok(())`，否则返回具体的Sm2Error
+  pub fn verify_public_key(&self, public_key: &str) -> Result<(), Sm2Error> {
+    self.furnish_valid_public_key_point(public_key).map(|_| ())
+  }
 
-  /// 验证公钥是否等价
-  pub fn compare_public_key_hex(&self, pub_k1: &str, pub_k2: &str) -> Result<bool, Sm2Error> {
+  /// 校验公钥并解析为椭圆曲线点，依次检查：
+  /// 1. 编码与长度；
+  /// 2. 不是无穷远点；
+  /// 3. 坐标`x`、`y`均在`[0, p - 1]`内；
+  /// 4. 满足曲线方程$y ^ 2 = x ^ 3 + ax + b$；
+  /// 5. $[n]P = O$。
+  pub(crate) fn furnish_valid_public_key_point(&self, public_key: &str) -> Result<ProjectivePoint, Sm2Error> {
     let ec_curve = &self.ec_curve;
-    let pub_k_point_1 = ec_curve.decode_point_hex(pub_k1).ok_or(Sm2Error::InvalidPublicKey)?;
-    let pub_k_point_2 = ec_curve.decode_point_hex(pub_k2).ok_or(Sm2Error::InvalidPublicKey)?;
+    // 编码、长度与坐标范围由decode_point_hex检查，压缩公钥只携带x，y由曲线方程导出
+    let pub_k_ec_point = ec_curve.decode_point_hex(public_key)?;
+    let pub_k_affine_point = pub_k_ec_point.to_affine();
+    let (Some(x), Some(y)) = (pub_k_affine_point.furnish_x(), pub_k_affine_point.furnish_y()) else {
+      return Err(Sm2Error::PointAtInfinity);
+    };
+    if y.sqr() != x.sqr().mul(x).add(&x.mul(&ec_curve.a)).add(&ec_curve.b) {
+      return Err(Sm2Error::PointNotOnCurve);
+    }
+
+    if !pub_k_ec_point.mul_vartime(&self.ec_n).judge_infty() {
+      return Err(Sm2Error::InvalidPointOrder);
+    }
 
-    Ok(pub_k_point_1 == pub_k_point_2)
+    Ok(pub_k_ec_point)
   }
 
 
-  /// sm3杂凑算法
-  fn sm3_hash_4sm2(
-    &mut self, hash_hex: &str, public_key: &str, user_id: Option<String>,
-  ) -> Result<String, Sm2Error> {
-    let uid = utf8_to_hex(&user_id.unwrap_or("1234567812345678".to_string()));
-
-    let ec_gpoint = &mut self.ec_gpoint;
-    let a = hex_left_zero_pad(&ec_gpoint.curve.a.furnish_item_big_uint().to_str_radix(16), 64);
-    let b = hex_left_zero_pad(&ec_gpoint.curve.b.furnish_item_big_uint().to_str_radix(16), 64);
-    let gpoint_x = hex_left_zero_pad(
-      &ec_gpoint.furnish_x().ok_or(Sm2Error::EllipticCurveError)?
-        .furnish_item_big_uint().to_str_radix(16),
-      64,
-    );
-    let gpoint_y = hex_left_zero_pad(
-      &ec_gpoint.furnish_y().ok_or(Sm2Error::EllipticCurveError)?
-        .furnish_item_big_uint().to_str_radix(16),
-      64,
-    );
+  /// 验证公钥是否等价
+  /// ## Parameters
+  /// - pub_k1, pub_k2: 公钥，支持十六进制（完整或压缩）、PEM格式的字符串以及Sm2PublicKey
+  pub fn compare_public_key_hex<K1: ConvertSm2PublicKey, K2: ConvertSm2PublicKey>(
+    &self, pub_k1: K1, pub_k2: K2,
+  ) -> Result<bool, Sm2Error> {
+    Ok(pub_k1.convert_to_sm2_public_key(self)? == pub_k2.convert_to_sm2_public_key(self)?)
+  }
 
-    let (pub_k_x, pub_k_y) = if public_key.len() == 128 {
-      (public_key[..64].to_string(), public_key[64..].to_string())
-    } else {
-      let mut pub_k_ec_point = ec_gpoint.curve.decode_point_hex(public_key)
-        .ok_or(Sm2Error::InvalidPublicKey)?;
-      let pub_k_ec_point_x = hex_left_zero_pad(
-        &pub_k_ec_point.furnish_x().ok_or(Sm2Error::EllipticCurveError)?
-          .furnish_item_big_uint().to_str_radix(16), 64);
-      let pub_k_ec_point_y = hex_left_zero_pad(
-        &pub_k_ec_point.furnish_y().ok_or(Sm2Error::EllipticCurveError)?
-          .furnish_item_big_uint().to_str_radix(16), 64);
-      (pub_k_ec_point_x, pub_k_ec_point_y)
-    };
 
-    let mut z_after_entl = hex_anly_arrs(
-      &[uid.as_str(), &a, &b, &gpoint_x, &gpoint_y, &pub_k_x, &pub_k_y].concat()
+  /// 计算用户标识的杂凑值$Z_A = SM3(ENTL_A \parallel ID_A \parallel a \parallel b \parallel x_G \parallel y_G \parallel x_A \parallel y_A)$
+  /// ## Parameters
+  /// - public_key: 用户公钥，支持十六进制字符串和Sm2PublicKey
+  /// - user_id: 用户ID，可为任意字节，至多8191字节
+  /// ## Returns
+  /// 32字节的$Z_A$，用户ID过长则返回Sm2Error::InvalidUserId
+  pub fn compute_za<K: ConvertSm2PublicKey>(
+    &self, public_key: K, user_id: &[u8],
+  ) -> Result<[u8; 32], Sm2Error> {
+    let public_key = public_key.convert_to_sm2_public_key(self)?;
+    let mut za = [0u8; 32];
+    za.copy_from_slice(&self.furnish_za(&public_key, user_id)?);
+    Ok(za)
+  }
+
+  /// 用户标识的杂凑值$Z_A$
+  fn furnish_za(&self, public_key: &Sm2PublicKey, user_id: &[u8]) -> Result<Vec<u8>, Sm2Error> {
+    // ENTL为用户ID的比特长度，占两个字节
+    if user_id.len() > SM2_MAX_USER_ID_LEN {
+      return Err(Sm2Error::InvalidUserId);
+    }
+    let entl = (user_id.len() * 8) as u16;
+
+    let ec_gpoint = self.ec_gpoint.to_affine();
+    let coordl = self.furnish_coord_len();
+    let a = big_uint_to_arrs(&self.ec_curve.a.furnish_item_big_uint(), coordl);
+    let b = big_uint_to_arrs(&self.ec_curve.b.furnish_item_big_uint(), coordl);
+    let gpoint_x = big_uint_to_arrs(
+      &ec_gpoint.furnish_x().ok_or(Sm2Error::EllipticCurveError)?.furnish_item_big_uint(), coordl,
     );
-    let entl = (uid.len() * 4) as u16;
-    // data.unshift(entl & 0x00ff)
-    z_after_entl.insert(0, (entl & 0xff) as u8);
-    // data.unshift(entl >> 8 & 0x00ff)
-    z_after_entl.insert(0, (entl >> 8) as u8);
+    let gpoint_y = big_uint_to_arrs(
+      &ec_gpoint.furnish_y().ok_or(Sm2Error::EllipticCurveError)?.furnish_item_big_uint(), coordl,
+    );
+    let pub_k_x = big_uint_to_arrs(&public_key.x, coordl);
+    let pub_k_y = big_uint_to_arrs(&public_key.y, coordl);
 
     // z = sm3(entl || id || a || b || gx || gy || px || py)
-    let z = sm3_digest(&z_after_entl);
-    Ok(arrs_to_hex(&sm3_digest(&[z, hex_anly_arrs(hash_hex)].concat())))
+    Ok(sm3_digest(&[
+      &entl.to_be_bytes()[..], user_id, &a, &b, &gpoint_x, &gpoint_y, &pub_k_x, &pub_k_y,
+    ].concat()))
+  }
+
+  /// sm3杂凑算法，$e = SM3(Z_A \parallel M)$
+  fn sm3_hash_4sm2(
+    &self, plain_arrs: &[u8], public_key: &Sm2PublicKey, user_id: &[u8],
+  ) -> Result<Vec<u8>, Sm2Error> {
+    let z = self.furnish_za(public_key, user_id)?;
+    Ok(sm3_digest(&[&z[..], plain_arrs].concat()))
   }
 
 
   /// 私钥导出公钥
   /// ## Parameters
   /// - private_key: 私钥
-  pub fn furnish_public_key_from_private_key(&mut self, private_key: &str) -> Result<String, Sm2Error> {
-    let ec_gpoint = &mut self.ec_gpoint;
-    let mut prv_k_gpoint = ec_gpoint.mul(
-      &BigUint::from_str_radix(private_key, 16).map_err(|_| Sm2Error::InvalidPrivateKey)?
-    );
-    let x = hex_left_zero_pad(&prv_k_gpoint.furnish_x().ok_or(Sm2Error::EllipticCurveError)?
-      .furnish_item_big_uint().to_str_radix(16), 64);
-    let y = hex_left_zero_pad(&prv_k_gpoint.furnish_y().ok_or(Sm2Error::EllipticCurveError)?
-      .furnish_item_big_uint().to_str_radix(16), 64);
+  pub fn furnish_public_key_from_private_key(&self, private_key: &str) -> Result<String, Sm2Error> {
+    let prv_k_gpoint = self.furnish_gpoint_mul(&Secret(
+      BigUint::from_str_radix(private_key, 16).map_err(|_| Sm2Error::InvalidPrivateKey)?
+    )).to_affine();
+    let x = self.furnish_coord_hex(&prv_k_gpoint.furnish_x().ok_or(Sm2Error::EllipticCurveError)?
+      .furnish_item_big_uint());
+    let y = self.furnish_coord_hex(&prv_k_gpoint.furnish_y().ok_or(Sm2Error::EllipticCurveError)?
+      .furnish_item_big_uint());
     Ok(format!("04{}{}", x, y))
   }
 
+  /// 按RFC 9380的`SM2P256V1_XMD:SM3_SSWU_RO_`套件将任意字节串映射为曲线上的点，
+  /// 其离散对数未知，可用于PAKE、VOPRF等协议；运算时间与消息无关，消息可以是口令
+  /// ## Parameters
+  /// - msg: 消息
+  /// - dst: 域分隔标签，应由应用名、版本与套件标识`SM2_H2C_SUITE_RO`组成
+  /// ## Returns
+  /// `04`前缀的十六进制点（sm2p256v1为130字符）；曲线不是sm2p256v1返回Sm2Error::UnsupportedAlgorithm，
+  /// 域分隔标签为空返回Sm2Error::InvalidDst
+  pub fn hash_to_curve(&self, msg: &[u8], dst: &[u8]) -> Result<String, Sm2Error> {
+    if !self.judge_sm2p256v1() {
+      return Err(Sm2Error::UnsupportedAlgorithm);
+    }
+    self.furnish_point_hex(&rfc9380::hash_to_curve(&self.ec_curve, msg, dst)?)
+  }
+
+  /// 按RFC 9380的`SM2P256V1_XMD:SM3_SSWU_NU_`套件将任意字节串映射为曲线上的点，
+  /// 比`hash_to_curve`少一次映射，但输出不是均匀分布的，只适用于协议明确允许的场合
+  /// ## Parameters
+  /// - msg: 消息
+  /// - dst: 域分隔标签，应由应用名、版本与套件标识`SM2_H2C_SUITE_NU`组成
+  /// ## Returns
+  /// 同`hash_to_curve`
+  pub fn encode_to_curve(&self, msg: &[u8], dst: &[u8]) -> Result<String, Sm2Error> {
+    if !self.judge_sm2p256v1() {
+      return Err(Sm2Error::UnsupportedAlgorithm);
+    }
+    self.furnish_point_hex(&rfc9380::encode_to_curve(&self.ec_curve, msg, dst)?)
+  }
+
+  /// 点的`04`前缀十六进制编码，无穷远点返回Sm2Error::PointAtInfinity
+  fn furnish_point_hex(&self, point: &ProjectivePoint) -> Result<String, Sm2Error> {
+    let affine_point = point.to_affine();
+    let (Some(x), Some(y)) = (affine_point.furnish_x(), affine_point.furnish_y()) else {
+      return Err(Sm2Error::PointAtInfinity);
+    };
+    Ok(format!(
+      "04{}{}",
+      self.furnish_coord_hex(&x.furnish_item_big_uint()),
+      self.furnish_coord_hex(&y.furnish_item_big_uint()),
+    ))
+  }
+
 
   /// 生成随机点
   fn get_point(&self) -> Result<Sm2RandomPoint, Sm2Error> {
-    let key_pair = self.generate_key_pair_hex(None)?;
-    let mut pub_k_ec_point = self.ec_curve.decode_point_hex(&key_pair.public_key)
-      .ok_or(Sm2Error::InvalidPublicKey)?;
-    let k = BigUint::from_str_radix(&key_pair.private_key, 16)
-      .map_err(|_| Sm2Error::InvalidPrivateKey)?;
-    let x1 = pub_k_ec_point.furnish_x().ok_or(Sm2Error::EllipticCurveError)?.furnish_item_big_uint();
+    self.furnish_point(self.generate_scalar(&(&self.ec_n - 1u8)))
+  }
+
+  /// 由给定的k计算点$[k]G$
+  fn furnish_point(&self, k: Secret<BigUint>) -> Result<Sm2RandomPoint, Sm2Error> {
+    let k_gpoint = self.furnish_gpoint_mul(&k).to_affine();
+    let x1 = k_gpoint.furnish_x().ok_or(Sm2Error::EllipticCurveError)?.furnish_item_big_uint();
     Ok(Sm2RandomPoint {
-      key_pair,
       k,
       x1,
     })
   }
+
+  /// 基点倍点$[k]G$，用于私钥、随机数`k`等秘密标量，查基点的预计算表而不做倍点运算
+  /// ## Parameters
+  /// - k: 标量，先对n取模
+  pub(crate) fn furnish_gpoint_mul(&self, k: &BigUint) -> ProjectivePoint {
+    let ec_gpoint_table = self.gpoint_table.get_or_init(|| {
+      if self.curve_params == CurveParams::sm2p256v1() {
+        SM2_GPOINT_TABLE.get_or_init(|| Arc::new(ECPointTable::new(&self.ec_gpoint))).clone()
+      } else {
+        Arc::new(ECPointTable::new(&self.ec_gpoint))
+      }
+    });
+    ec_gpoint_table.mul(&self.ec_curve, &Secret(k % &self.ec_n))
+  }
+
+  /// 基点G的奇数倍点表，供批量验签使用
+  fn furnish_gpoint_odd_multiples(&self) -> &[ProjectivePoint] {
+    self.gpoint_odd_multiples.get_or_init(|| {
+      if self.curve_params == CurveParams::sm2p256v1() {
+        SM2_GPOINT_ODD_MULTIPLES.get_or_init(|| Arc::new(self.ec_gpoint.furnish_odd_multiples())).clone()
+      } else {
+        Arc::new(self.ec_gpoint.furnish_odd_multiples())
+      }
+    })
+  }
 }
 
 
 pub trait Sm2CryptoTrait {
-  fn encrypt<S>(
-    &self, plain_text: S, public_key: S, sm2_mode_kind: Sm2ModeKind,
+  fn encrypt<S, K>(
+    &self, plain_text: S, public_key: K, sm2_mode_kind: Sm2ModeKind,
   ) -> Result<String, Sm2Error> where
-    S: AsRef<str>;
-  fn decrypt<S>(
-    &self, cipher_text: S, private_key: S, sm2_mode_kind: Sm2ModeKind,
+    S: AsRef<str>,
+    K: ConvertSm2PublicKey;
+  fn decrypt<S, K>(
+    &self, cipher_text: S, private_key: K, sm2_mode_kind: Sm2ModeKind,
   ) -> Result<String, Sm2Error> where
-    S: AsRef<str>;
+    S: AsRef<str>,
+    K: ConvertSm2PrivateKey;
 }
 
 
@@ -281,40 +574,37 @@ impl Sm2CryptoTrait for Sm2 {
   /// SM2加密
   /// ## Parameters
   /// - plain_text: 明文，支持字符串类型
-  /// - public_key: 公钥，支持字符串类型
+  /// - public_key: 公钥，支持十六进制字符串和Sm2PublicKey
   /// - sm2_mode_kind: 加密模式
   /// ## Returns
   /// SM2加密密文结果，失败则返回Sm2Error
-  fn encrypt<S>(
-    &self, plain_text: S, public_key: S, sm2_mode_kind: Sm2ModeKind,
+  fn encrypt<S, K>(
+    &self, plain_text: S, public_key: K, sm2_mode_kind: Sm2ModeKind,
   ) -> Result<String, Sm2Error> where
     S: AsRef<str>,
+    K: ConvertSm2PublicKey,
   {
     let mut plain_text_arrs = hex_anly_arrs(&utf8_to_hex(plain_text.as_ref()));
-    let pub_k_ec_point = self.ec_curve.decode_point_hex(public_key.as_ref())
-      .ok_or(Sm2Error::InvalidPublicKey)?;
-
-    let key_pairs = self.generate_key_pair_hex(None)?;
-    let prv_k_rad = BigUint::from_str_radix(&key_pairs.private_key, 16)
-      .map_err(|_| Sm2Error::InvalidPrivateKey)?;
-
-    let mut c1 = key_pairs.public_key;
-    if c1.len() > 128 {
-      c1 = c1[c1.len() - 128..].to_string();
-    }
-
-    let mut rad_point = pub_k_ec_point.mul(&prv_k_rad);
-    let x2 = hex_anly_arrs(&hex_left_zero_pad(
-      &rad_point.furnish_x().ok_or(Sm2Error::EllipticCurveError)?
-        .furnish_item_big_uint().to_str_radix(16), 64)
+    let pub_k_ec_point = public_key.convert_to_sm2_public_key(self)?.point;
+
+    let prv_k_rad = self.generate_scalar(&(&self.ec_n - 1u8));
+
+    // c1 = [k]G，去掉前缀04
+    let c1_point = self.furnish_gpoint_mul(&prv_k_rad).to_affine();
+    let c1 = format!(
+      "{}{}",
+      self.furnish_coord_hex(&c1_point.furnish_x().ok_or(Sm2Error::EllipticCurveError)?
+        .furnish_item_big_uint()),
+      self.furnish_coord_hex(&c1_point.furnish_y().ok_or(Sm2Error::EllipticCurveError)?
+        .furnish_item_big_uint()),
     );
-    let y2 = hex_anly_arrs(&hex_left_zero_pad(
-      &rad_point.furnish_y().ok_or(Sm2Error::EllipticCurveError)?
-        .furnish_item_big_uint().to_str_radix(16), 64)
-    );
-    let c3 = arrs_to_hex(&sm3_digest(&[&x2[..], &plain_text_arrs[..], &y2[..]].concat()));
 
-    Self::crypto_message_digest_xor(&mut plain_text_arrs, x2.clone(), y2.clone());
+    let rad_point = pub_k_ec_point.mul(&prv_k_rad);
+    let x2y2 = self.furnish_shared_point_arrs(&rad_point)?;
+    let (x2, y2) = x2y2.split_at(self.furnish_coord_len());
+    let c3 = arrs_to_hex(&sm3_digest(&Secret([x2, &plain_text_arrs[..], y2].concat())));
+
+    Self::crypto_message_digest_xor(&mut plain_text_arrs, x2, y2);
     let c2 = arrs_to_hex(&plain_text_arrs);
 
     if sm2_mode_kind == Sm2ModeKind::C1C2C3 {
@@ -327,41 +617,44 @@ impl Sm2CryptoTrait for Sm2 {
   /// SM2解密
   /// ## Parameters
   /// - cipher_text: 密文，支持字符串类型
-  /// - private_key: 私钥，支持字符串类型
+  /// - private_key: 私钥，支持十六进制字符串和Sm2PrivateKey
   /// - sm2_mode_kind: 解密模式
   /// ## Returns
   /// SM2解密明文结果，失败则返回Sm2Error
-  fn decrypt<S>(
-    &self, cipher_text: S, private_key: S, sm2_mode_kind: Sm2ModeKind,
+  fn decrypt<S, K>(
+    &self, cipher_text: S, private_key: K, sm2_mode_kind: Sm2ModeKind,
   ) -> Result<String, Sm2Error> where
     S: AsRef<str>,
+    K: ConvertSm2PrivateKey,
   {
     let cipher_text = cipher_text.as_ref();
-    let private_key = BigUint::from_str_radix(private_key.as_ref(), 16).unwrap();
+    let private_key = private_key.convert_to_sm2_private_key(self)?;
 
+    // C1为两个坐标，C3为32字节的SM3杂凑值
+    let c1l = 4 * self.furnish_coord_len();
+    if !cipher_text.bytes().all(|byt| byt.is_ascii_hexdigit()) {
+      return Err(Sm2Error::CodingError);
+    }
+    if cipher_text.len() < c1l + 64 {
+      return Err(Sm2Error::DecryptionError);
+    }
     let (c2, c3) = if sm2_mode_kind == Sm2ModeKind::C1C2C3 {
-      (&cipher_text[128..cipher_text.len() - 64], &cipher_text[cipher_text.len() - 64..])
+      (&cipher_text[c1l..cipher_text.len() - 64], &cipher_text[cipher_text.len() - 64..])
     } else {
-      (&cipher_text[192..], &cipher_text[128..192])
+      (&cipher_text[c1l + 64..], &cipher_text[c1l..c1l + 64])
     };
 
-    let ec_cv = &self.ec_curve;
-    let c1 = ec_cv.decode_point_hex(&format!("04{}", &cipher_text[0..128]))
-      .ok_or(Sm2Error::InvalidPublicKey)?;
-    let mut rad_point = c1.mul(&private_key);
-    let x2 = hex_anly_arrs(&hex_left_zero_pad(
-      &rad_point.furnish_x().ok_or(Sm2Error::EllipticCurveError)?
-        .furnish_item_big_uint().to_str_radix(16), 64)
-    );
-    let y2 = hex_anly_arrs(&hex_left_zero_pad(
-      &rad_point.furnish_y().ok_or(Sm2Error::EllipticCurveError)?
-        .furnish_item_big_uint().to_str_radix(16), 64)
-    );
+    // C1须与公钥一样校验，否则不在曲线上的点与私钥相乘会泄露私钥（无效曲线攻击）
+    let c1 = self.furnish_valid_public_key_point(&format!("04{}", &cipher_text[0..c1l]))
+      .map_err(|_| Sm2Error::InvalidPublicKey)?;
+    let rad_point = c1.mul(&private_key.d);
+    let x2y2 = self.furnish_shared_point_arrs(&rad_point)?;
+    let (x2, y2) = x2y2.split_at(self.furnish_coord_len());
 
-    let mut c2_arrs = hex_anly_arrs(c2);
-    Self::crypto_message_digest_xor(&mut c2_arrs, x2.clone(), y2.clone());
+    let mut c2_arrs = Secret(hex_anly_arrs(c2));
+    Self::crypto_message_digest_xor(&mut c2_arrs, x2, y2);
 
-    let check_c3 = arrs_to_hex(&sm3_digest(&[&x2[..], &c2_arrs[..], &y2[..]].concat()));
+    let check_c3 = arrs_to_hex(&sm3_digest(&Secret([x2, &c2_arrs[..], y2].concat())));
     if check_c3.to_lowercase() == c3.to_lowercase() {
       Ok(arrs_to_utf8_latin1(&c2_arrs).ok_or(Sm2Error::CodingError)?)
     } else {
@@ -371,17 +664,247 @@ impl Sm2CryptoTrait for Sm2 {
 }
 
 
+/// 流式SM2签名器：创建时计算$Z_A$，之后可分多次输入明文，适用于无法一次读入内存的大文件；
+/// 同时实现了`io::Write`，可直接作为`io::copy`的目标
+pub struct Sm2Signer<'a> {
+  sm2: &'a Sm2,
+  private_key: Sm2PrivateKey,
+  sm3_hasher: Sm3Hasher,
+}
+
+
+/// 流式SM2验签器，用法与Sm2Signer相同
+pub struct Sm2Verifier<'a> {
+  sm2: &'a Sm2,
+  public_key: Sm2PublicKey,
+  sm3_hasher: Sm3Hasher,
+}
+
+
+impl Sm2Signer<'_> {
+  /// 输入一段明文
+  pub fn update(&mut self, plain_arrs: &[u8]) {
+    self.sm3_hasher.update(plain_arrs);
+  }
+
+  /// 结束输入并签名
+  /// ## Parameters
+  /// - need_der: 是否返回DER格式的签名
+  /// ## Returns
+  /// SM2签名结果，程序错误则返回Sm2Error
+  pub fn sign(self, need_der: bool) -> Result<String, Sm2Error> {
+    let sign_options = self.sm2.furnish_sign_options(need_der, true, None, None)?;
+    self.sign_with_options(sign_options)
+  }
+
+  /// 结束输入并按选项签名
+  /// ## Parameters
+  /// - sign_options: 签名选项，其中的杂凑方式、用户ID和公钥不参与签名，$Z_A$已在创建签名器时确定
+  /// ## Returns
+  /// SM2签名结果，程序错误则返回Sm2Error
+  pub fn sign_with_options(self, sign_options: Sm2SignOptions<'_>) -> Result<String, Sm2Error> {
+    let e = BigUint::from_bytes_be(&self.sm3_hasher.finalize());
+    let need_der = sign_options.furnish_need_der();
+    let nonce_kind = sign_options.nonce_kind.unwrap_or(Sm2NonceKind::Random);
+    self.sm2.sign_digest(&e, &self.private_key, need_der, nonce_kind, sign_options.rng)
+  }
+}
+
+
+impl Sm2Verifier<'_> {
+  /// 输入一段明文
+  pub fn update(&mut self, plain_arrs: &[u8]) {
+    self.sm3_hasher.update(plain_arrs);
+  }
+
+  /// 结束输入并验签
+  /// ## Parameters
+  /// - sign_text: 签名，支持字符串类型
+  /// - need_der: 是否使用DER格式的签名
+  /// ## Returns
+  /// SM2验签结果，程序错误则返回Sm2Error
+  pub fn verify<S: AsRef<str>>(self, sign_text: S, need_der: bool) -> Result<bool, Sm2Error> {
+    let e = BigUint::from_bytes_be(&self.sm3_hasher.finalize());
+    self.sm2.verify_digest(&e, sign_text.as_ref(), &self.public_key, need_der)
+  }
+}
+
+
+/// 为流式签名器和验签器实现`io::Write`
+macro_rules! impl_write_for_sm2_stream {
+  ($($t:ident),*) => {
+    $(
+      impl io::Write for $t<'_> {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+          self.update(buf);
+          Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+          Ok(())
+        }
+      }
+    )*
+  };
+}
+
+impl_write_for_sm2_stream!(Sm2Signer, Sm2Verifier);
+
+
+/// 签名值的编码格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sm2SignatureEncoding {
+  // r || s，各64字符的十六进制字符串
+  Raw,
+  // DER编码的SEQUENCE { r INTEGER, s INTEGER }的十六进制字符串
+  Der,
+}
+
+
+/// 待签名消息的杂凑方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sm2HashMode {
+  // $e = SM3(Z_A \parallel M)$
+  Za,
+  // 不杂凑，明文即为杂凑值e
+  None,
+  // 明文为已计算好的32字节杂凑值e
+  Prehashed,
+}
+
+
+/// SM2签名与验签选项，以构建器的方式逐项设置，缺省为：
+/// 十六进制的r || s编码、$SM3(Z_A \parallel M)$杂凑、默认用户ID、私钥缓存的公钥、由随机数发生器生成k
+/// - encoding: 签名值的编码格式
+/// - hash_mode: 待签名消息的杂凑方式
+/// - user_id: 用户ID，可为任意字节，至多8191字节，缺省为`1234567812345678`
+/// - public_key: 计算$Z_A$所用的签名者公钥，仅签名时使用，缺省为私钥缓存的公钥
+/// - nonce_kind: 随机数k的生成方式，仅签名时使用
+/// - rng: 本次签名所用的随机数发生器，缺省为Sm2的随机数发生器
+#[derive(Default)]
+pub struct Sm2SignOptions<'a> {
+  encoding: Option<Sm2SignatureEncoding>,
+  hash_mode: Option<Sm2HashMode>,
+  user_id: Option<Vec<u8>>,
+  public_key: Option<Sm2PublicKey>,
+  nonce_kind: Option<Sm2NonceKind>,
+  rng: Option<&'a mut dyn RngCore>,
+}
+
+
+impl<'a> Sm2SignOptions<'a> {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// 设置签名值的编码格式
+  pub fn encoding(mut self, encoding: Sm2SignatureEncoding) -> Self {
+    self.encoding = Some(encoding);
+    self
+  }
+
+  /// 设置待签名消息的杂凑方式
+  pub fn hash_mode(mut self, hash_mode: Sm2HashMode) -> Self {
+    self.hash_mode = Some(hash_mode);
+    self
+  }
+
+  /// 设置用户ID
+  pub fn user_id<U: AsRef<[u8]>>(mut self, user_id: U) -> Self {
+    self.user_id = Some(user_id.as_ref().to_vec());
+    self
+  }
+
+  /// 设置计算$Z_A$所用的签名者公钥
+  pub fn public_key(mut self, public_key: Sm2PublicKey) -> Self {
+    self.public_key = Some(public_key);
+    self
+  }
+
+  /// 设置随机数k的生成方式
+  pub fn nonce_kind(mut self, nonce_kind: Sm2NonceKind) -> Self {
+    self.nonce_kind = Some(nonce_kind);
+    self
+  }
+
+  /// 设置本次签名所用的随机数发生器
+  pub fn rng<R: CryptoRng + RngCore>(mut self, rng: &'a mut R) -> Self {
+    self.rng = Some(rng);
+    self
+  }
+
+  fn furnish_need_der(&self) -> bool {
+    self.encoding == Some(Sm2SignatureEncoding::Der)
+  }
+
+  fn furnish_user_id(&self) -> &[u8] {
+    self.user_id.as_deref().unwrap_or(SM2_DEFAULT_USER_ID)
+  }
+}
+
+
+/// 批量验签的一项
+/// - message: 消息字节数组，按$SM3(Z_A \parallel M)$杂凑
+/// - signature: 签名值
+/// - public_key: 签名者公钥，支持十六进制字符串、PEM格式的SubjectPublicKeyInfo和`&Sm2PublicKey`
+/// - user_id: 用户ID，缺省为`1234567812345678`
+#[derive(Debug, Clone, Copy)]
+pub struct Sm2BatchEntry<'a, K = &'a str> {
+  pub message: &'a [u8],
+  pub signature: &'a str,
+  pub public_key: K,
+  pub user_id: Option<&'a [u8]>,
+}
+
+
+/// 批量验签在各项之间共享的预计算：按公钥缓存的解析校验结果与倍点表、按(公钥, 用户ID)缓存的$Z_A$；
+/// 基点G的奇数倍点表则由Sm2缓存
+struct Sm2BatchCache<'a, K> {
+  public_keys: HashMap<K, Option<(Sm2PublicKey, Vec<ProjectivePoint>)>>,
+  zas: HashMap<(K, &'a [u8]), Option<Vec<u8>>>,
+}
+
+
+/// 兼容接口：以布尔值和可选字符串传参，均由`Sm2::sign_with`和`Sm2::verify_with`实现
 pub trait Sm2SignTrait {
-  fn sign<S>(
-    &mut self, plain_text: S, private_key: S, need_der: bool, need_hash: bool,
+  fn sign<S, K>(
+    &self, plain_text: S, private_key: K, need_der: bool, need_hash: bool,
+    public_key: Option<String>, user_id: Option<String>,
+  ) -> Result<String, Sm2Error> where
+    S: AsRef<str>,
+    K: ConvertSm2PrivateKey;
+  fn sign_deterministic<S, K>(
+    &self, plain_text: S, private_key: K, need_der: bool, need_hash: bool,
+    public_key: Option<String>, user_id: Option<String>,
+  ) -> Result<String, Sm2Error> where
+    S: AsRef<str>,
+    K: ConvertSm2PrivateKey;
+  fn sign_bytes<K>(
+    &self, plain_arrs: &[u8], private_key: K, need_der: bool, need_hash: bool,
     public_key: Option<String>, user_id: Option<String>,
   ) -> Result<String, Sm2Error> where
-    S: AsRef<str>;
-  fn verify<S>(
-    &mut self, plain_text: S, sign_text: S, public_key: S, need_der: bool, need_hash: bool,
+    K: ConvertSm2PrivateKey;
+  fn sign_prehashed<K>(
+    &self, digest: &[u8; 32], private_key: K, need_der: bool,
+  ) -> Result<String, Sm2Error> where
+    K: ConvertSm2PrivateKey;
+  fn verify<S, K>(
+    &self, plain_text: S, sign_text: S, public_key: K, need_der: bool, need_hash: bool,
+    user_id: Option<String>,
+  ) -> Result<bool, Sm2Error> where
+    S: AsRef<str>,
+    K: ConvertSm2PublicKey;
+  fn verify_bytes<S, K>(
+    &self, plain_arrs: &[u8], sign_text: S, public_key: K, need_der: bool, need_hash: bool,
     user_id: Option<String>,
   ) -> Result<bool, Sm2Error> where
-    S: AsRef<str>;
+    S: AsRef<str>,
+    K: ConvertSm2PublicKey;
+  fn verify_prehashed<S, K>(
+    &self, digest: &[u8; 32], sign_text: S, public_key: K, need_der: bool,
+  ) -> Result<bool, Sm2Error> where
+    S: AsRef<str>,
+    K: ConvertSm2PublicKey;
 }
 
 
@@ -389,126 +912,355 @@ impl Sm2SignTrait for Sm2 {
   /// SM2签名
   /// ## Parameters
   /// - plain_text: 明文，支持字符串类型
-  /// - private_key: 私钥，支持字符串类型
+  /// - private_key: 私钥，支持十六进制字符串和Sm2PrivateKey
   /// - need_der: 是否返回DER格式的签名
   /// - need_hash: 是否对明文进行杂凑
-  /// - public_key: 额外的公钥，在需要对明文进行杂凑时使用
+  /// - public_key: 额外的公钥，在需要对明文进行杂凑时使用，缺省时使用私钥缓存的公钥
   /// - user_id: 额外的用户ID
   /// ## Returns
   /// SM2签名结果，程序错误则返回Sm2Error
-  fn sign<S>(
-    &mut self, plain_text: S, private_key: S, need_der: bool, need_hash: bool,
+  fn sign<S, K>(
+    &self, plain_text: S, private_key: K, need_der: bool, need_hash: bool,
     public_key: Option<String>, user_id: Option<String>,
   ) -> Result<String, Sm2Error> where
     S: AsRef<str>,
+    K: ConvertSm2PrivateKey,
   {
-    let mut plain_text = utf8_to_hex(plain_text.as_ref());
+    self.sign_bytes(plain_text.as_ref().as_bytes(), private_key, need_der, need_hash, public_key, user_id)
+  }
 
-    if need_hash {
-      let public_key = public_key.unwrap_or(
-        self.furnish_public_key_from_private_key(private_key.as_ref())?);
-      plain_text = self.sm3_hash_4sm2(&plain_text, &public_key, user_id)?;
+  /// SM2确定性签名，随机数k按RFC 6979以HMAC-SM3由私钥和待签名杂凑值派生，不依赖随机数发生器
+  /// ## Parameters
+  /// - plain_text: 明文，支持字符串类型
+  /// - private_key: 私钥，支持十六进制字符串和Sm2PrivateKey
+  /// - need_der: 是否返回DER格式的签名
+  /// - need_hash: 是否对明文进行杂凑
+  /// - public_key: 额外的公钥，在需要对明文进行杂凑时使用，缺省时使用私钥缓存的公钥
+  /// - user_id: 额外的用户ID
+  /// ## Returns
+  /// SM2签名结果，相同输入的结果相同；程序错误则返回Sm2Error
+  fn sign_deterministic<S, K>(
+    &self, plain_text: S, private_key: K, need_der: bool, need_hash: bool,
+    public_key: Option<String>, user_id: Option<String>,
+  ) -> Result<String, Sm2Error> where
+    S: AsRef<str>,
+    K: ConvertSm2PrivateKey,
+  {
+    let sign_options = self.furnish_sign_options(need_der, need_hash, public_key, user_id)?
+      .nonce_kind(Sm2NonceKind::Deterministic);
+    self.sign_with(plain_text.as_ref().as_bytes(), private_key, sign_options)
+  }
+
+  /// SM2签名，明文为任意字节数组
+  /// ## Parameters
+  /// - plain_arrs: 明文字节数组
+  /// - private_key: 私钥，支持十六进制字符串和Sm2PrivateKey
+  /// - need_der: 是否返回DER格式的签名
+  /// - need_hash: 是否对明文进行杂凑，否则明文即为待签名的杂凑值
+  /// - public_key: 额外的公钥，在需要对明文进行杂凑时使用，缺省时使用私钥缓存的公钥
+  /// - user_id: 额外的用户ID
+  /// ## Returns
+  /// SM2签名结果，程序错误则返回Sm2Error
+  fn sign_bytes<K>(
+    &self, plain_arrs: &[u8], private_key: K, need_der: bool, need_hash: bool,
+    public_key: Option<String>, user_id: Option<String>,
+  ) -> Result<String, Sm2Error> where
+    K: ConvertSm2PrivateKey,
+  {
+    let sign_options = self.furnish_sign_options(need_der, need_hash, public_key, user_id)?;
+    self.sign_with(plain_arrs, private_key, sign_options)
+  }
+
+  /// 对已计算好的杂凑值e签名
+  /// ## Parameters
+  /// - digest: 32字节的杂凑值$e = SM3(Z_A \parallel M)$，可由`digest_message`计算
+  /// - private_key: 私钥，支持十六进制字符串和Sm2PrivateKey
+  /// - need_der: 是否返回DER格式的签名
+  /// ## Returns
+  /// SM2签名结果，程序错误则返回Sm2Error
+  fn sign_prehashed<K>(
+    &self, digest: &[u8; 32], private_key: K, need_der: bool,
+  ) -> Result<String, Sm2Error> where
+    K: ConvertSm2PrivateKey,
+  {
+    let sign_options = self.furnish_sign_options(need_der, true, None, None)?
+      .hash_mode(Sm2HashMode::Prehashed);
+    self.sign_with(digest, private_key, sign_options)
+  }
+
+  /// SM2验签
+  /// ## Parameters
+  /// - plain_text: 明文，支持字符串类型
+  /// - sign_text: 签名，支持字符串类型
+  /// - public_key: 公钥，支持十六进制字符串和Sm2PublicKey
+  /// - need_der: 是否使用DER格式的签名
+  /// - need_hash: 是否对明文进行杂凑
+  /// - user_id: 额外的用户ID
+  /// ## Returns
+  /// SM2验签结果，程序错误则返回Sm2Error
+  fn verify<S, K>(
+    &self, plain_text: S, sign_text: S, public_key: K, need_der: bool, need_hash: bool,
+    user_id: Option<String>,
+  ) -> Result<bool, Sm2Error> where
+    S: AsRef<str>,
+    K: ConvertSm2PublicKey,
+  {
+    self.verify_bytes(plain_text.as_ref().as_bytes(), sign_text, public_key, need_der, need_hash, user_id)
+  }
+
+  /// SM2验签，明文为任意字节数组
+  /// ## Parameters
+  /// - plain_arrs: 明文字节数组
+  /// - sign_text: 签名，支持字符串类型
+  /// - public_key: 公钥，支持十六进制字符串和Sm2PublicKey
+  /// - need_der: 是否使用DER格式的签名
+  /// - need_hash: 是否对明文进行杂凑，否则明文即为待验证的杂凑值
+  /// - user_id: 额外的用户ID
+  /// ## Returns
+  /// SM2验签结果，程序错误则返回Sm2Error
+  fn verify_bytes<S, K>(
+    &self, plain_arrs: &[u8], sign_text: S, public_key: K, need_der: bool, need_hash: bool,
+    user_id: Option<String>,
+  ) -> Result<bool, Sm2Error> where
+    S: AsRef<str>,
+    K: ConvertSm2PublicKey,
+  {
+    let sign_options = self.furnish_sign_options(need_der, need_hash, None, user_id)?;
+    self.verify_with(plain_arrs, sign_text, public_key, sign_options)
+  }
+
+  /// 验证对已计算好的杂凑值e的签名
+  /// ## Parameters
+  /// - digest: 32字节的杂凑值$e = SM3(Z_A \parallel M)$，可由`digest_message`计算
+  /// - sign_text: 签名，支持字符串类型
+  /// - public_key: 公钥，支持十六进制字符串和Sm2PublicKey
+  /// - need_der: 是否使用DER格式的签名
+  /// ## Returns
+  /// SM2验签结果，程序错误则返回Sm2Error
+  fn verify_prehashed<S, K>(
+    &self, digest: &[u8; 32], sign_text: S, public_key: K, need_der: bool,
+  ) -> Result<bool, Sm2Error> where
+    S: AsRef<str>,
+    K: ConvertSm2PublicKey,
+  {
+    let sign_options = self.furnish_sign_options(need_der, true, None, None)?
+      .hash_mode(Sm2HashMode::Prehashed);
+    self.verify_with(digest, sign_text, public_key, sign_options)
+  }
+}
+
+
+impl Sm2 {
+  /// 按选项签名
+  /// ## Parameters
+  /// - plain_arrs: 明文字节数组；杂凑方式为Prehashed时为32字节的杂凑值e
+  /// - private_key: 私钥，支持十六进制字符串和Sm2PrivateKey
+  /// - sign_options: 签名选项
+  /// ## Returns
+  /// SM2签名结果，程序错误则返回Sm2Error
+  pub fn sign_with<K: ConvertSm2PrivateKey>(
+    &self, plain_arrs: &[u8], private_key: K, sign_options: Sm2SignOptions<'_>,
+  ) -> Result<String, Sm2Error> {
+    let private_key = private_key.convert_to_sm2_private_key(self)?;
+    let public_key = sign_options.public_key.as_ref().unwrap_or(private_key.public_key());
+    let e = self.furnish_digest(
+      plain_arrs, public_key, sign_options.hash_mode.unwrap_or(Sm2HashMode::Za), sign_options.furnish_user_id(),
+    )?;
+    let need_der = sign_options.furnish_need_der();
+    let nonce_kind = sign_options.nonce_kind.unwrap_or(Sm2NonceKind::Random);
+    self.sign_digest(&e, &private_key, need_der, nonce_kind, sign_options.rng)
+  }
+
+  /// 按选项验签
+  /// ## Parameters
+  /// - plain_arrs: 明文字节数组；杂凑方式为Prehashed时为32字节的杂凑值e
+  /// - sign_text: 签名，支持字符串类型
+  /// - public_key: 公钥，支持十六进制字符串和Sm2PublicKey
+  /// - sign_options: 签名选项，其中的公钥、随机数k的生成方式和随机数发生器不参与验签
+  /// ## Returns
+  /// SM2验签结果，程序错误则返回Sm2Error
+  pub fn verify_with<S: AsRef<str>, K: ConvertSm2PublicKey>(
+    &self, plain_arrs: &[u8], sign_text: S, public_key: K, sign_options: Sm2SignOptions<'_>,
+  ) -> Result<bool, Sm2Error> {
+    let public_key = public_key.convert_to_sm2_public_key(self)?;
+    let e = self.furnish_digest(
+      plain_arrs, &public_key, sign_options.hash_mode.unwrap_or(Sm2HashMode::Za), sign_options.furnish_user_id(),
+    )?;
+    self.verify_digest(&e, sign_text.as_ref(), &public_key, sign_options.furnish_need_der())
+  }
+
+  /// 由兼容接口的参数构造签名选项
+  fn furnish_sign_options(
+    &self, need_der: bool, need_hash: bool, public_key: Option<String>, user_id: Option<String>,
+  ) -> Result<Sm2SignOptions<'static>, Sm2Error> {
+    let mut sign_options = Sm2SignOptions::new()
+      .encoding(if need_der { Sm2SignatureEncoding::Der } else { Sm2SignatureEncoding::Raw })
+      .hash_mode(if need_hash { Sm2HashMode::Za } else { Sm2HashMode::None });
+    if let Some(public_key) = public_key {
+      // 兼容不带`04`前缀的公钥（sm2p256v1为128字符）
+      let public_key = if public_key.len() == 4 * self.furnish_coord_len() {
+        format!("04{}", public_key)
+      } else {
+        public_key
+      };
+      sign_options = sign_options.public_key(Sm2PublicKey::from_hex(self, &public_key)?);
+    }
+    if let Some(user_id) = user_id {
+      sign_options = sign_options.user_id(user_id);
     }
+    Ok(sign_options)
+  }
+
+  /// 计算待签名的杂凑值$e = SM3(Z_A \parallel M)$，供`sign_prehashed`和`verify_prehashed`使用
+  /// ## Parameters
+  /// - plain_arrs: 明文字节数组
+  /// - public_key: 签名者公钥，支持十六进制字符串和Sm2PublicKey
+  /// - user_id: 额外的用户ID，可为任意字节，缺省为`1234567812345678`
+  /// ## Returns
+  /// 32字节的杂凑值，程序错误则返回Sm2Error
+  pub fn digest_message<K: ConvertSm2PublicKey>(
+    &self, plain_arrs: &[u8], public_key: K, user_id: Option<&[u8]>,
+  ) -> Result<[u8; 32], Sm2Error> {
+    let public_key = public_key.convert_to_sm2_public_key(self)?;
+    let user_id = user_id.unwrap_or(SM2_DEFAULT_USER_ID);
+    let mut digest = [0u8; 32];
+    digest.copy_from_slice(&self.sm3_hash_4sm2(plain_arrs, &public_key, user_id)?);
+    Ok(digest)
+  }
+
+  /// 创建流式签名器
+  /// ## Parameters
+  /// - private_key: 私钥，支持十六进制字符串和Sm2PrivateKey
+  /// - user_id: 额外的用户ID，可为任意字节，缺省为`1234567812345678`
+  /// ## Returns
+  /// 已输入$Z_A$的签名器，程序错误则返回Sm2Error
+  pub fn signer<K: ConvertSm2PrivateKey>(
+    &self, private_key: K, user_id: Option<&[u8]>,
+  ) -> Result<Sm2Signer<'_>, Sm2Error> {
+    let private_key = private_key.convert_to_sm2_private_key(self)?;
+    let user_id = user_id.unwrap_or(SM2_DEFAULT_USER_ID);
+    let mut sm3_hasher = Sm3Hasher::new();
+    sm3_hasher.update(&self.furnish_za(private_key.public_key(), user_id)?);
+    Ok(Sm2Signer { sm2: self, private_key, sm3_hasher })
+  }
 
-    let d_a_u = BigUint::from_str_radix(private_key.as_ref(), 16)
-      .map_err(|_| Sm2Error::InvalidPrivateKey)?;
-    let e_u = BigUint::from_str_radix(&plain_text, 16)
-      .map_err(|_| Sm2Error::InvalidData)?;
-    let n_u = self.ec_n.clone();
+  /// 创建流式验签器
+  /// ## Parameters
+  /// - public_key: 公钥，支持十六进制字符串和Sm2PublicKey
+  /// - user_id: 额外的用户ID，可为任意字节，缺省为`1234567812345678`
+  /// ## Returns
+  /// 已输入$Z_A$的验签器，程序错误则返回Sm2Error
+  pub fn verifier<K: ConvertSm2PublicKey>(
+    &self, public_key: K, user_id: Option<&[u8]>,
+  ) -> Result<Sm2Verifier<'_>, Sm2Error> {
+    let public_key = public_key.convert_to_sm2_public_key(self)?;
+    let user_id = user_id.unwrap_or(SM2_DEFAULT_USER_ID);
+    let mut sm3_hasher = Sm3Hasher::new();
+    sm3_hasher.update(&self.furnish_za(&public_key, user_id)?);
+    Ok(Sm2Verifier { sm2: self, public_key, sm3_hasher })
+  }
 
-    let d_a = BigInt::from_biguint(Sign::Plus, d_a_u);
-    let e = BigInt::from_biguint(Sign::Plus, e_u);
-    let n = BigInt::from_biguint(Sign::Plus, n_u);
+  /// 按杂凑方式计算杂凑值e
+  fn furnish_digest(
+    &self, plain_arrs: &[u8], public_key: &Sm2PublicKey, hash_mode: Sm2HashMode, user_id: &[u8],
+  ) -> Result<BigUint, Sm2Error> {
+    match hash_mode {
+      | Sm2HashMode::Za => {
+        Ok(BigUint::from_bytes_be(&self.sm3_hash_4sm2(plain_arrs, public_key, user_id)?))
+      }
+      // 不杂凑时，明文直接作为杂凑值e
+      | Sm2HashMode::None if plain_arrs.is_empty() => Err(Sm2Error::InvalidData),
+      | Sm2HashMode::Prehashed if plain_arrs.len() != 32 => Err(Sm2Error::InvalidData),
+      | Sm2HashMode::None | Sm2HashMode::Prehashed => Ok(BigUint::from_bytes_be(plain_arrs)),
+    }
+  }
+
+  /// 对杂凑值e签名
+  fn sign_digest(
+    &self, e: &BigUint, private_key: &Sm2PrivateKey, need_der: bool, nonce_kind: Sm2NonceKind,
+    mut rng: Option<&mut dyn RngCore>,
+  ) -> Result<String, Sm2Error> {
+    let d_a = &private_key.d;
+    let n = &self.ec_n;
+    // (1 + dA) ^ -1 mod n
+    let d_a_inv = Secret((&**d_a + 1u8).modpow(&(n - 2u8), n));
+    let mut nonce_generator = match nonce_kind {
+      | Sm2NonceKind::Random => None,
+      | Sm2NonceKind::Deterministic => Some(Sm3NonceGenerator::new(d_a, e, n)),
+    };
 
     let (r_u, s_u) = loop {
-      let sm2_random_point = self.get_point()?;
-      let k = BigInt::from_biguint(Sign::Plus, sm2_random_point.k.clone());
-      let x1 = BigInt::from_biguint(Sign::Plus, sm2_random_point.x1);
+      let sm2_random_point = match (&mut nonce_generator, &mut rng) {
+        | (Some(nonce_generator), _) => self.furnish_point(nonce_generator.next_nonce())?,
+        | (None, Some(rng)) => self.furnish_point(Self::generate_scalar_from(&mut **rng, &(n - 1u8)))?,
+        | (None, None) => self.get_point()?,
+      };
+      let k = &sm2_random_point.k;
 
-      let rdm = (&e + &x1) % &n;
-      if rdm.is_zero() || (&rdm + &k) == n {
+      let rdm = (e + &sm2_random_point.x1) % n;
+      if rdm.is_zero() || (&rdm + &**k) == *n {
         continue;
       }
 
       // s = dA.add(BigInteger.ONE).modInverse(n).multiply(k.subtract(r.multiply(dA))).mod(n)
-      let s = big_int_mod_floor(&((&k - &rdm * &d_a)
-        * (&d_a + BigInt::one()).modpow(&(n.clone() - 2u32), &n)), &n);
+      let r_d_a = Secret(&rdm * &**d_a % n);
+      let k_sub_r_d_a = Secret((&**k + n - &*r_d_a) % n);
+      let s = &*k_sub_r_d_a * &*d_a_inv % n;
 
       if !s.is_zero() {
-        let r_u = rdm.to_biguint().unwrap();
-        let s_u = s.to_biguint().unwrap();
-        break (r_u, s_u);
+        break (rdm, s);
       }
     };
 
     Ok(if need_der {
-      encode_der(
-        &BigInt::from_biguint(Sign::Plus, r_u),
-        &BigInt::from_biguint(Sign::Plus, s_u),
-      )
+      encode_der(&r_u, &s_u)?
     } else {
       format!(
         "{}{}",
-        hex_left_zero_pad(&r_u.to_str_radix(16), 64),
-        hex_left_zero_pad(&s_u.to_str_radix(16), 64)
+        self.furnish_coord_hex(&r_u),
+        self.furnish_coord_hex(&s_u)
       )
     })
   }
 
-  /// SM2验签
-  /// ## Parameters
-  /// - plain_text: 明文，支持字符串类型
-  /// - sign_text: 签名，支持字符串类型
-  /// - public_key: 公钥，支持字符串类型
-  /// - need_der: 是否使用DER格式的签名
-  /// - need_hash: 是否对明文进行杂凑
-  /// - user_id: 额外的用户ID
-  /// ## Returns
-  /// SM2验签结果，程序错误则返回Sm2Error
-  fn verify<S>(
-    &mut self, plain_text: S, sign_text: S, public_key: S, need_der: bool, need_hash: bool,
-    user_id: Option<String>,
-  ) -> Result<bool, Sm2Error> where
-    S: AsRef<str>,
-  {
-    let public_key = public_key.as_ref();
-    let sign_text = sign_text.as_ref();
-    let mut plain_text = utf8_to_hex(plain_text.as_ref());
-
-    if need_hash {
-      plain_text = self.sm3_hash_4sm2(&plain_text, public_key, user_id)?;
-    }
+  /// 验证对杂凑值e的签名
+  fn verify_digest(
+    &self, e: &BigUint, sign_text: &str, public_key: &Sm2PublicKey, need_der: bool,
+  ) -> Result<bool, Sm2Error> {
+    self.verify_digest_by(e, sign_text, need_der, |s, t| self.ec_gpoint.mul_add_vartime(s, &public_key.point, t))
+  }
 
+  /// 验证对杂凑值e的签名，$[s]G + [t]P_A$由调用方给出，以便批量验签时复用倍点表
+  fn verify_digest_by<F: FnOnce(&BigUint, &BigUint) -> ProjectivePoint>(
+    &self, e: &BigUint, sign_text: &str, need_der: bool, mul_add: F,
+  ) -> Result<bool, Sm2Error> {
+    // 编码不合规时返回Sm2Error
     let (r, s) = if need_der {
-      let (r_int, s_int) = decode_der(sign_text);
-
-      // 负数判断，不合法直接返回 false
-      let r = match r_int.to_biguint() {
-        Some(val) => val,
-        None => return Ok(false),
-      };
-      let s = match s_int.to_biguint() {
-        Some(val) => val,
-        None => return Ok(false),
-      };
-      (r, s)
+      decode_der(sign_text)?
     } else {
+      let coord_hexl = 2 * self.furnish_coord_len();
+      if sign_text.len() != 2 * coord_hexl || !sign_text.bytes().all(|byt| byt.is_ascii_hexdigit()) {
+        return Err(Sm2Error::InvalidData);
+      }
       (
-        BigUint::from_str_radix(&sign_text[..64], 16).map_err(|_| Sm2Error::InvalidData)?,
-        BigUint::from_str_radix(&sign_text[64..], 16).map_err(|_| Sm2Error::InvalidData)?,
+        BigUint::from_str_radix(&sign_text[..coord_hexl], 16).map_err(|_| Sm2Error::InvalidData)?,
+        BigUint::from_str_radix(&sign_text[coord_hexl..], 16).map_err(|_| Sm2Error::InvalidData)?,
       )
     };
 
-    let p_a = self.ec_curve.decode_point_hex(public_key);
-    let e = BigUint::from_str_radix(&plain_text, 16).map_err(|_| Sm2Error::InvalidData)?;
+    // r, s须在[1, n - 1]内，不合法直接返回 false
+    if r.is_zero() || s.is_zero() || r >= self.ec_n || s >= self.ec_n {
+      return Ok(false);
+    }
+
     let t = (&r + &s) % &self.ec_n;
 
     if t.is_zero() {
       return Ok(false);
     }
 
-    let mut x1y1 = self.ec_gpoint.mul(&s).add(&p_a.ok_or(Sm2Error::EllipticCurveError)?.mul(&t));
-    let r_check = (&e + x1y1.furnish_x().ok_or(Sm2Error::EllipticCurveError)?
+    let x1y1 = mul_add(&s, &t).to_affine();
+    let r_check = (e + x1y1.furnish_x().ok_or(Sm2Error::EllipticCurveError)?
       .furnish_item_big_uint()) % &self.ec_n;
 
     Ok(r == r_check)
@@ -516,14 +1268,91 @@ impl Sm2SignTrait for Sm2 {
 }
 
 
-/// 计算大整数的模运算（向下取整），确保结果始终为非负数。
-/// - dial: 被除数
-/// - n: 除数（必须为正数）
-fn big_int_mod_floor(dial: &BigInt, n: &BigInt) -> BigInt {
-  let reap = dial % n;
-  if reap.sign() == Sign::Minus {
-    (reap + n) % n
-  } else {
-    reap
+/// 单线程批量验签的最小项数，少于该数时不拆分线程
+#[cfg(feature = "parallel")]
+const SM2_BATCH_PARALLEL_MIN: usize = 64;
+
+
+impl Sm2 {
+  /// 批量验签，各项依次按$SM3(Z_A \parallel M)$杂凑后验签；
+  /// 基点G的倍点表只生成一次，各公钥的解析校验结果与倍点表及$Z_A$在整批中只计算一次。
+  /// 开启`parallel`特性时按可用的CPU核数分块多线程验签
+  /// ## Parameters
+  /// - entries: 待验签的各项
+  /// - need_der: 签名值是否为DER编码
+  /// ## Returns
+  /// 验签失败的项的下标（升序），签名不匹配、签名或公钥编码错误、用户ID过长均记为失败
+  pub fn verify_batch<K>(&self, entries: &[Sm2BatchEntry<'_, K>], need_der: bool) -> Vec<usize> where
+    K: ConvertSm2PublicKey + Copy + Eq + Hash + Sync,
+  {
+    #[cfg(feature = "parallel")]
+    {
+      let threadl = std::thread::available_parallelism().map_or(1, |threadl| threadl.get());
+      if threadl > 1 && entries.len() >= SM2_BATCH_PARALLEL_MIN {
+        let chunkl = entries.len().div_ceil(threadl);
+        return std::thread::scope(|scope| {
+          let handles: Vec<_> = entries.chunks(chunkl).enumerate().map(|(chunki, chunk)| {
+            scope.spawn(move || {
+              self.verify_batch_chunk(chunk, need_der).into_iter()
+                .map(|i| chunki * chunkl + i).collect::<Vec<_>>()
+            })
+          }).collect();
+          handles.into_iter()
+            .flat_map(|handle| handle.join().expect("SM2 batch verification thread panicked"))
+            .collect()
+        });
+      }
+    }
+
+    self.verify_batch_chunk(entries, need_der)
+  }
+
+  /// 在当前线程内批量验签
+  fn verify_batch_chunk<'a, K>(&self, entries: &[Sm2BatchEntry<'a, K>], need_der: bool) -> Vec<usize> where
+    K: ConvertSm2PublicKey + Copy + Eq + Hash,
+  {
+    let mut batch_cache = Sm2BatchCache {
+      public_keys: HashMap::new(),
+      zas: HashMap::new(),
+    };
+
+    entries.iter().enumerate()
+      .filter(|(_, entry)| !self.verify_batch_entry(entry, need_der, &mut batch_cache))
+      .map(|(i, _)| i)
+      .collect()
+  }
+
+  /// 验证批量中的一项，任何错误均视为验签失败
+  fn verify_batch_entry<'a, K>(
+    &self, entry: &Sm2BatchEntry<'a, K>, need_der: bool, batch_cache: &mut Sm2BatchCache<'a, K>,
+  ) -> bool where
+    K: ConvertSm2PublicKey + Copy + Eq + Hash,
+  {
+    let cached_public_key = batch_cache.public_keys.entry(entry.public_key).or_insert_with(|| {
+      entry.public_key.convert_to_sm2_public_key(self).ok().map(|public_key| {
+        let public_key_table = public_key.point.furnish_odd_multiples();
+        (public_key, public_key_table)
+      })
+    });
+    let Some((public_key, public_key_table)) = cached_public_key else {
+      return false;
+    };
+
+    let user_id = entry.user_id.unwrap_or(SM2_DEFAULT_USER_ID);
+    let za = batch_cache.zas.entry((entry.public_key, user_id))
+      .or_insert_with(|| self.furnish_za(public_key, user_id).ok());
+    let Some(za) = za else {
+      return false;
+    };
+
+    let mut sm3_hasher = Sm3Hasher::new();
+    sm3_hasher.update(za);
+    sm3_hasher.update(entry.message);
+    let e = BigUint::from_bytes_be(&sm3_hasher.finalize());
+
+    let gpoint_table = self.furnish_gpoint_odd_multiples();
+    self.verify_digest_by(&e, entry.signature, need_der, |s, t| {
+      ProjectivePoint::mul_add_from_tables(gpoint_table, s, public_key_table, t)
+    }).unwrap_or(false)
   }
 }
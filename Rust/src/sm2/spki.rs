@@ -0,0 +1,72 @@
+#![allow(deprecated)]
+
+
+use der::asn1::BitString;
+use der::{Decode, Encode, Sequence};
+use crate::sm2::key::*;
+use crate::sm2::pkcs8::{AlgorithmIdentifierDer, der_to_pem, pem_anly_der};
+use crate::sm2::sm2::{Sm2, Sm2Error};
+
+
+const PEM_LABEL_SPKI: &str = "PUBLIC KEY";
+
+
+/// SubjectPublicKeyInfo ::= SEQUENCE { algorithm AlgorithmIdentifier, subjectPublicKey BIT STRING }
+#[derive(Clone, Debug, Eq, PartialEq, Sequence)]
+pub(crate) struct SubjectPublicKeyInfoDer {
+  pub algorithm: AlgorithmIdentifierDer,
+  pub subject_public_key: BitString,
+}
+
+
+impl SubjectPublicKeyInfoDer {
+  /// 解析为SM2公钥，公钥按GM/T 0003校验
+  pub(crate) fn to_sm2_public_key(&self, sm2: &Sm2) -> Result<Sm2PublicKey, Sm2Error> {
+    self.algorithm.check_sm2()?;
+    if !sm2.judge_sm2p256v1() {
+      return Err(Sm2Error::UnsupportedAlgorithm);
+    }
+    let pub_k_arrs = self.subject_public_key.as_bytes().ok_or(Sm2Error::Asn1Error)?;
+    Sm2PublicKey::from_bytes(sm2, pub_k_arrs)
+  }
+
+  /// 由SM2公钥构造，公钥以非压缩格式编码
+  pub(crate) fn from_sm2_public_key(public_key: &Sm2PublicKey) -> Result<Self, Sm2Error> {
+    if !public_key.judge_sm2p256v1() {
+      return Err(Sm2Error::UnsupportedAlgorithm);
+    }
+    Ok(Self {
+      algorithm: AlgorithmIdentifierDer::sm2()?,
+      subject_public_key: BitString::from_bytes(&public_key.to_bytes()).map_err(|_| Sm2Error::Asn1Error)?,
+    })
+  }
+}
+
+
+impl Sm2PublicKey {
+  /// 解析DER编码的SubjectPublicKeyInfo
+  /// ## Parameters
+  /// - sm2: SM2椭圆曲线参数
+  /// - der_arrs: DER编码的公钥
+  pub fn from_public_key_der(sm2: &Sm2, der_arrs: &[u8]) -> Result<Self, Sm2Error> {
+    SubjectPublicKeyInfoDer::from_der(der_arrs).map_err(|_| Sm2Error::Asn1Error)?.to_sm2_public_key(sm2)
+  }
+
+  /// 编码为DER格式的SubjectPublicKeyInfo
+  pub fn to_public_key_der(&self) -> Result<Vec<u8>, Sm2Error> {
+    SubjectPublicKeyInfoDer::from_sm2_public_key(self)?.to_der().map_err(|_| Sm2Error::Asn1Error)
+  }
+
+  /// 解析PEM编码的SubjectPublicKeyInfo，标签为`PUBLIC KEY`
+  /// ## Parameters
+  /// - sm2: SM2椭圆曲线参数
+  /// - pem_talks: PEM编码的公钥
+  pub fn from_public_key_pem(sm2: &Sm2, pem_talks: &str) -> Result<Self, Sm2Error> {
+    Self::from_public_key_der(sm2, &pem_anly_der(pem_talks, &[PEM_LABEL_SPKI])?)
+  }
+
+  /// 编码为PEM格式的SubjectPublicKeyInfo
+  pub fn to_public_key_pem(&self) -> Result<String, Sm2Error> {
+    der_to_pem(PEM_LABEL_SPKI, &self.to_public_key_der()?)
+  }
+}
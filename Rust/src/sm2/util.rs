@@ -1,6 +1,11 @@
+#![allow(deprecated)]
+
+
 use der::{Encode, Decode, Sequence};
 use der::asn1::UintRef;
-use num_bigint::BigInt;
+use num_bigint::BigUint;
+use crate::secret::Secret;
+use crate::sm2::sm2::Sm2Error;
 
 
 /// SM2签名的der元素r和s
@@ -13,32 +18,34 @@ struct Sm2SignDerRS<'a> {
 
 /// SM2签名 der编码
 /// ## Parameters
-/// - r: sm2的`r`，必须是正数
-/// - s: sm2签名的`s`，必须是正数
-pub fn encode_der(r: &BigInt, s: &BigInt) -> String {
-  let r_byt = r.to_bytes_be().1;
-  let s_byt = s.to_bytes_be().1;
+/// - r: sm2签名的`r`
+/// - s: sm2签名的`s`
+/// ## Returns
+/// DER编码的十六进制字符串，编码失败则返回Sm2Error
+pub fn encode_der(r: &BigUint, s: &BigUint) -> Result<String, Sm2Error> {
+  let r_byt = r.to_bytes_be();
+  let s_byt = s.to_bytes_be();
 
   let sm2_sig_der = Sm2SignDerRS {
-    r: UintRef::new(&r_byt).unwrap(),
-    s: UintRef::new(&s_byt).unwrap(),
+    r: UintRef::new(&r_byt).map_err(|_| Sm2Error::Asn1Error)?,
+    s: UintRef::new(&s_byt).map_err(|_| Sm2Error::Asn1Error)?,
   };
 
-  hex::encode(sm2_sig_der.to_der().unwrap())
+  Ok(hex::encode(sm2_sig_der.to_der().map_err(|_| Sm2Error::Asn1Error)?))
 }
 
 
-/// SM2签名 der解码
+/// SM2签名 der解码，仅接受规范的DER编码：拒绝末尾多余数据、非最短长度和非最短或负数的整数
 /// ## Parameters
 /// - sg_talks: sm2签名的DER编码字符串
 /// ## Returns
-/// sm2签名的`r`和`s`
-pub fn decode_der(sg_talks: &str) -> (BigInt, BigInt) {
-  let sg_byts = hex::decode(sg_talks).unwrap();
-  let sig = Sm2SignDerRS::from_der(sg_byts.as_slice()).unwrap();
-  let r = BigInt::from_bytes_be(num_bigint::Sign::Plus, sig.r.as_bytes());
-  let s = BigInt::from_bytes_be(num_bigint::Sign::Plus, sig.s.as_bytes());
-  (r, s)
+/// sm2签名的`r`和`s`，十六进制格式错误返回Sm2Error::CodingError，DER编码不规范返回Sm2Error::Asn1Error
+pub fn decode_der(sg_talks: &str) -> Result<(BigUint, BigUint), Sm2Error> {
+  let sg_byts = hex::decode(sg_talks).map_err(|_| Sm2Error::CodingError)?;
+  let sig = Sm2SignDerRS::from_der(sg_byts.as_slice()).map_err(|_| Sm2Error::Asn1Error)?;
+  let r = BigUint::from_bytes_be(sig.r.as_bytes());
+  let s = BigUint::from_bytes_be(sig.s.as_bytes());
+  Ok((r, s))
 }
 
 
@@ -67,11 +74,9 @@ pub fn utf8_to_hex(utf8_talks: &str) -> String {
 /// ## Parameters
 /// - arrs: 字节数组
 pub fn arrs_to_utf8_latin1(arrs: &[u8]) -> Option<String> {
-  let mut word_arrs = vec![0u32; (arrs.len() + 3) / 4];
-  let mut j = 0;
-  for i in (0..arrs.len() * 2).step_by(2) {
+  let mut word_arrs = vec![0u32; arrs.len().div_ceil(4)];
+  for (j, i) in (0..arrs.len() * 2).step_by(2).enumerate() {
     word_arrs[i >> 3] |= (arrs[j] as u32) << (24 - (i % 8) * 4);
-    j += 1;
   }
 
   let mut latin1_chs = Vec::new();
@@ -79,10 +84,7 @@ pub fn arrs_to_utf8_latin1(arrs: &[u8]) -> Option<String> {
     latin1_chs.push(((word_arrs[i >> 2] >> (24 - (i % 4) * 8)) & 0xff) as u8);
   }
 
-  match String::from_utf8(latin1_chs) {
-    Ok(latin1_talk) => Some(latin1_talk),
-    Err(_) => None,
-  }
+  String::from_utf8(latin1_chs).ok()
 }
 
 
@@ -94,13 +96,34 @@ pub fn arrs_to_hex(byt_arrs: &[u8]) -> String {
 }
 
 
+/// 大整数转32字节的大端字节数组，不足时左侧补0
+/// ## Parameters
+/// - dial: 不超过256比特的大整数
+pub fn big_uint_to_arrs32(dial: &BigUint) -> Vec<u8> {
+  big_uint_to_arrs(dial, 32)
+}
+
+
+/// 大整数转指定长度的大端字节数组，不足时左侧补0
+/// ## Parameters
+/// - dial: 不超过`arrsl`字节的大整数
+/// - arrsl: 字节数组长度
+pub fn big_uint_to_arrs(dial: &BigUint, arrsl: usize) -> Vec<u8> {
+  // 常用于共享点等秘密值，中间结果同样需要擦除
+  let dial_arrs = Secret(dial.to_bytes_be());
+  let mut reap = vec![0u8; arrsl.saturating_sub(dial_arrs.len())];
+  reap.extend_from_slice(&dial_arrs);
+  reap
+}
+
+
 /// 解读十六进制字符串为数组
 /// ## Parameters
 /// - hex_talks: 十六进制字符串
 /// ## Returns
 /// 十六进制字符串对应的数组，例如：`hex_anly_arrs("4f20") == [79, 32]`
 pub fn hex_anly_arrs(hex_talks: &str) -> Vec<u8> {
-  if hex_talks.len() % 2 != 0 {
+  if !hex_talks.len().is_multiple_of(2) {
     hex_left_zero_pad(hex_talks, hex_talks.len() + 1)
   } else {
     hex_talks.to_string()
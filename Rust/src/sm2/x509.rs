@@ -0,0 +1,897 @@
+#![allow(deprecated)]
+
+
+use std::net::IpAddr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use der::asn1::{
+  Any, BitString, GeneralizedTime, Ia5String, Int, ObjectIdentifier, OctetString, PrintableString, SetOfVec,
+  UtcTime, Uint,
+};
+use der::{Choice, Decode, Encode, Sequence, Tag, TagNumber, Tagged, ValueOrd};
+use crate::sm2::key::*;
+use crate::sm2::pkcs8::{AlgorithmIdentifierDer, der_to_pem, pem_anly_der};
+use crate::sm2::sm2::{Sm2, Sm2Error, Sm2SignTrait};
+use crate::sm2::spki::SubjectPublicKeyInfoDer;
+use crate::sm2::util::*;
+
+
+/// SM2-with-SM3签名算法
+pub(crate) const OID_SM2_WITH_SM3: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.156.10197.1.501");
+/// basicConstraints扩展
+pub(crate) const OID_BASIC_CONSTRAINTS: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.29.19");
+/// keyUsage扩展
+pub(crate) const OID_KEY_USAGE: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.29.15");
+/// subjectAltName扩展
+pub(crate) const OID_SUBJECT_ALT_NAME: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.29.17");
+/// extKeyUsage扩展
+const OID_EXT_KEY_USAGE: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.29.37");
+/// subjectKeyIdentifier扩展
+const OID_SUBJECT_KEY_IDENTIFIER: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.29.14");
+/// authorityKeyIdentifier扩展
+const OID_AUTHORITY_KEY_IDENTIFIER: ObjectIdentifier = ObjectIdentifier::new_unwrap("2.5.29.35");
+
+/// 链验证时能够识别的扩展，其余扩展若标记为关键则拒绝
+const KNOWN_EXTENSIONS: [ObjectIdentifier; 6] = [
+  OID_BASIC_CONSTRAINTS, OID_KEY_USAGE, OID_SUBJECT_ALT_NAME, OID_EXT_KEY_USAGE,
+  OID_SUBJECT_KEY_IDENTIFIER, OID_AUTHORITY_KEY_IDENTIFIER,
+];
+
+/// 证书链的最大长度
+const MAX_CHAIN_DEPTH: usize = 8;
+
+const PEM_LABEL_CERTIFICATE: &str = "CERTIFICATE";
+
+/// 属性类型的简称，用于输出可读的名称
+pub(crate) const NAME_ATTRS: [(&str, ObjectIdentifier); 8] = [
+  ("CN", ObjectIdentifier::new_unwrap("2.5.4.3")),
+  ("serialNumber", ObjectIdentifier::new_unwrap("2.5.4.5")),
+  ("C", ObjectIdentifier::new_unwrap("2.5.4.6")),
+  ("L", ObjectIdentifier::new_unwrap("2.5.4.7")),
+  ("ST", ObjectIdentifier::new_unwrap("2.5.4.8")),
+  ("O", ObjectIdentifier::new_unwrap("2.5.4.10")),
+  ("OU", ObjectIdentifier::new_unwrap("2.5.4.11")),
+  ("emailAddress", ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.1")),
+];
+
+
+/// AttributeTypeAndValue
+#[derive(Clone, Debug, Eq, PartialEq, Sequence, ValueOrd)]
+pub(crate) struct AttributeTypeAndValueDer {
+  pub attr_type: ObjectIdentifier,
+  pub attr_value: Any,
+}
+
+
+/// Name ::= SEQUENCE OF RelativeDistinguishedName
+pub(crate) type NameDer = Vec<SetOfVec<AttributeTypeAndValueDer>>;
+
+
+/// Time ::= CHOICE { utcTime UTCTime, generalTime GeneralizedTime }
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Choice)]
+pub(crate) enum TimeDer {
+  #[asn1(type = "UTCTime")]
+  Utc(UtcTime),
+  #[asn1(type = "GeneralizedTime")]
+  General(GeneralizedTime),
+}
+
+
+impl TimeDer {
+  /// 2050年之前使用UTCTime，之后使用GeneralizedTime（RFC 5280 4.1.2.5）
+  fn from_system_time(time: SystemTime) -> Result<Self, Sm2Error> {
+    let unix_duration = time.duration_since(UNIX_EPOCH).map_err(|_| Sm2Error::InvalidData)?;
+    match UtcTime::from_unix_duration(unix_duration) {
+      | Ok(utc_time) => Ok(Self::Utc(utc_time)),
+      | Err(_) => Ok(Self::General(
+        GeneralizedTime::from_unix_duration(unix_duration).map_err(|_| Sm2Error::InvalidData)?
+      )),
+    }
+  }
+
+  fn to_system_time(self) -> SystemTime {
+    UNIX_EPOCH + match self {
+      | Self::Utc(utc_time) => utc_time.to_unix_duration(),
+      | Self::General(general_time) => general_time.to_unix_duration(),
+    }
+  }
+}
+
+
+/// Validity
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Sequence)]
+pub(crate) struct ValidityDer {
+  pub not_before: TimeDer,
+  pub not_after: TimeDer,
+}
+
+
+/// Extension
+#[derive(Clone, Debug, Eq, PartialEq, Sequence)]
+pub(crate) struct ExtensionDer {
+  pub extn_id: ObjectIdentifier,
+  #[asn1(default = "Default::default")]
+  pub critical: bool,
+  pub extn_value: OctetString,
+}
+
+
+/// BasicConstraints
+#[derive(Clone, Debug, Default, Eq, PartialEq, Sequence)]
+pub(crate) struct BasicConstraintsDer {
+  #[asn1(default = "Default::default")]
+  pub ca: bool,
+  pub path_len_constraint: Option<u32>,
+}
+
+
+/// TBSCertificate
+#[derive(Clone, Debug, Eq, PartialEq, Sequence)]
+pub(crate) struct TbsCertificateDer {
+  #[asn1(context_specific = "0", tag_mode = "EXPLICIT", default = "Default::default")]
+  pub version: u8,
+  pub serial_number: Int,
+  pub signature: AlgorithmIdentifierDer,
+  pub issuer: NameDer,
+  pub validity: ValidityDer,
+  pub subject: NameDer,
+  pub subject_public_key_info: SubjectPublicKeyInfoDer,
+  #[asn1(context_specific = "1", tag_mode = "IMPLICIT", optional = "true")]
+  pub issuer_unique_id: Option<BitString>,
+  #[asn1(context_specific = "2", tag_mode = "IMPLICIT", optional = "true")]
+  pub subject_unique_id: Option<BitString>,
+  #[asn1(context_specific = "3", tag_mode = "EXPLICIT", optional = "true")]
+  pub extensions: Option<Vec<ExtensionDer>>,
+}
+
+
+/// Certificate，TBSCertificate保留原始编码以便验签
+#[derive(Clone, Debug, Eq, PartialEq, Sequence)]
+struct CertificateDer {
+  tbs_certificate: Any,
+  signature_algorithm: AlgorithmIdentifierDer,
+  signature_value: BitString,
+}
+
+
+/// SM2签名值 ::= SEQUENCE { r INTEGER, s INTEGER }
+#[derive(Clone, Debug, Eq, PartialEq, Sequence)]
+pub(crate) struct Sm2SignatureDer {
+  pub r: Uint,
+  pub s: Uint,
+}
+
+
+/// 将Name转化为可读的字符串，如`C=CN, O=Example, CN=example.com`
+pub(crate) fn name_to_string(name: &NameDer) -> String {
+  name.iter().flat_map(|rdn| rdn.iter()).map(|attr| {
+    let attr_type = NAME_ATTRS.iter().find(|(_, oid)| *oid == attr.attr_type)
+      .map(|(short_name, _)| short_name.to_string())
+      .unwrap_or_else(|| attr.attr_type.to_string());
+    let attr_value = attr.attr_value.value();
+    let attr_value = match attr.attr_value.tag() {
+      // BMPString为UTF-16BE
+      | Tag::BmpString => String::from_utf16_lossy(
+        &attr_value.chunks(2).map(|ch| u16::from_be_bytes([ch[0], *ch.get(1).unwrap_or(&0)])).collect::<Vec<_>>()
+      ),
+      | _ => String::from_utf8_lossy(attr_value).to_string(),
+    };
+    format!("{}={}", attr_type, attr_value)
+  }).collect::<Vec<_>>().join(", ")
+}
+
+
+/// SM2-with-SM3签名算法标识，参数可省略或为NULL
+pub(crate) fn check_sm2_with_sm3(alg: &AlgorithmIdentifierDer) -> Result<(), Sm2Error> {
+  if alg.algorithm != OID_SM2_WITH_SM3 || alg.parameters.as_ref().is_some_and(|param| param.tag() != Tag::Null) {
+    return Err(Sm2Error::UnsupportedAlgorithm);
+  }
+  Ok(())
+}
+
+
+/// 使用默认用户ID验证DER编码的SM2签名值
+pub(crate) fn verify_sm2_signature(
+  sm2: &Sm2, plain_arrs: &[u8], sign_arrs: &[u8], public_key: &Sm2PublicKey,
+) -> Result<bool, Sm2Error> {
+  let sign = match Sm2SignatureDer::from_der(sign_arrs) {
+    | Ok(sign) => sign,
+    | Err(_) => return Ok(false),
+  };
+  if sign.r.as_bytes().len() > 32 || sign.s.as_bytes().len() > 32 {
+    return Ok(false);
+  }
+  let sign_hex = format!(
+    "{}{}",
+    hex_left_zero_pad(&arrs_to_hex(sign.r.as_bytes()), 64),
+    hex_left_zero_pad(&arrs_to_hex(sign.s.as_bytes()), 64)
+  );
+  sm2.verify_bytes(plain_arrs, &sign_hex, public_key, false, true, None)
+}
+
+
+/// SM2-with-SM3签名的X.509证书
+/// - der_arrs: 证书的DER编码
+/// - tbs_arrs: TBSCertificate的DER编码，即签名的对象
+/// - tbs: 解析后的TBSCertificate
+/// - signature: DER编码的签名值
+/// - public_key: 证书主体的公钥
+#[derive(Clone, Debug)]
+pub struct Sm2Certificate {
+  der_arrs: Vec<u8>,
+  tbs_arrs: Vec<u8>,
+  tbs: TbsCertificateDer,
+  signature: Vec<u8>,
+  public_key: Sm2PublicKey,
+}
+
+
+impl Sm2Certificate {
+  /// 解析DER编码的X.509证书，要求签名算法为SM2-with-SM3，主体公钥为SM2公钥
+  /// ## Parameters
+  /// - sm2: SM2椭圆曲线参数
+  /// - der_arrs: DER编码的证书
+  pub fn from_der(sm2: &Sm2, der_arrs: &[u8]) -> Result<Self, Sm2Error> {
+    let cert = CertificateDer::from_der(der_arrs).map_err(|_| Sm2Error::Asn1Error)?;
+    let tbs = cert.tbs_certificate.decode_as::<TbsCertificateDer>().map_err(|_| Sm2Error::Asn1Error)?;
+    if tbs.version > 2 {
+      return Err(Sm2Error::InvalidCertificate);
+    }
+
+    // 外层与TBSCertificate内的签名算法须一致
+    check_sm2_with_sm3(&cert.signature_algorithm)?;
+    if tbs.signature != cert.signature_algorithm {
+      return Err(Sm2Error::InvalidCertificate);
+    }
+
+    let public_key = tbs.subject_public_key_info.to_sm2_public_key(sm2)?;
+    Ok(Self {
+      der_arrs: der_arrs.to_vec(),
+      tbs_arrs: cert.tbs_certificate.to_der().map_err(|_| Sm2Error::Asn1Error)?,
+      signature: cert.signature_value.as_bytes().ok_or(Sm2Error::Asn1Error)?.to_vec(),
+      tbs,
+      public_key,
+    })
+  }
+
+  /// 解析PEM编码的X.509证书，标签为`CERTIFICATE`
+  /// ## Parameters
+  /// - sm2: SM2椭圆曲线参数
+  /// - pem_talks: PEM编码的证书
+  pub fn from_pem(sm2: &Sm2, pem_talks: &str) -> Result<Self, Sm2Error> {
+    Self::from_der(sm2, &pem_anly_der(pem_talks, &[PEM_LABEL_CERTIFICATE])?)
+  }
+
+  /// 证书的DER编码
+  pub fn to_der(&self) -> &[u8] {
+    &self.der_arrs
+  }
+
+  /// 证书的PEM编码
+  pub fn to_pem(&self) -> Result<String, Sm2Error> {
+    der_to_pem(PEM_LABEL_CERTIFICATE, &self.der_arrs)
+  }
+
+  /// 证书版本，1、2或3
+  pub fn version(&self) -> u8 {
+    self.tbs.version + 1
+  }
+
+  /// 十六进制格式的序列号
+  pub fn serial_number_hex(&self) -> String {
+    arrs_to_hex(self.tbs.serial_number.as_bytes())
+  }
+
+  /// 颁发者名称，如`C=CN, O=Example, CN=Example CA`
+  pub fn issuer(&self) -> String {
+    name_to_string(&self.tbs.issuer)
+  }
+
+  /// 主体名称
+  pub fn subject(&self) -> String {
+    name_to_string(&self.tbs.subject)
+  }
+
+  /// 有效期起始时间
+  pub fn not_before(&self) -> SystemTime {
+    self.tbs.validity.not_before.to_system_time()
+  }
+
+  /// 有效期截止时间
+  pub fn not_after(&self) -> SystemTime {
+    self.tbs.validity.not_after.to_system_time()
+  }
+
+  /// 证书主体的SM2公钥
+  pub fn public_key(&self) -> &Sm2PublicKey {
+    &self.public_key
+  }
+
+  /// 判断给定时间是否在有效期内
+  /// ## Parameters
+  /// - time: 待判断的时间
+  pub fn is_valid_at(&self, time: SystemTime) -> bool {
+    self.not_before() <= time && time <= self.not_after()
+  }
+
+  /// 颁发者与主体名称相同
+  pub fn is_self_issued(&self) -> bool {
+    self.tbs.issuer == self.tbs.subject
+  }
+
+  /// 扩展项
+  fn extension(&self, extn_id: ObjectIdentifier) -> Option<&ExtensionDer> {
+    self.tbs.extensions.as_ref()?.iter().find(|extn| extn.extn_id == extn_id)
+  }
+
+  /// basicConstraints扩展，缺省时视为非CA证书
+  fn basic_constraints(&self) -> Result<BasicConstraintsDer, Sm2Error> {
+    match self.extension(OID_BASIC_CONSTRAINTS) {
+      | Some(extn) => BasicConstraintsDer::from_der(extn.extn_value.as_bytes()).map_err(|_| Sm2Error::Asn1Error),
+      | None => Ok(BasicConstraintsDer::default()),
+    }
+  }
+
+  /// 是否为CA证书
+  pub fn is_ca(&self) -> bool {
+    self.basic_constraints().is_ok_and(|basic_constraints| basic_constraints.ca)
+  }
+
+  /// 能否签发证书：basicConstraints中cA为真，且keyUsage（若存在）包含keyCertSign
+  fn can_sign_certificate(&self) -> Result<bool, Sm2Error> {
+    if !self.basic_constraints()?.ca {
+      return Ok(false);
+    }
+    match self.extension(OID_KEY_USAGE) {
+      | Some(extn) => {
+        let key_usage = BitString::from_der(extn.extn_value.as_bytes()).map_err(|_| Sm2Error::Asn1Error)?;
+        // keyCertSign为第5位
+        Ok(key_usage.raw_bytes().first().is_some_and(|byt| byt & 0x04 != 0))
+      }
+      | None => Ok(true),
+    }
+  }
+
+  /// 存在无法识别的关键扩展
+  fn has_unknown_critical_extension(&self) -> bool {
+    self.tbs.extensions.iter().flatten()
+      .any(|extn| extn.critical && !KNOWN_EXTENSIONS.contains(&extn.extn_id))
+  }
+
+  /// 使用颁发者公钥验证证书签名，用户ID为默认的`1234567812345678`
+  /// ## Parameters
+  /// - sm2: SM2椭圆曲线参数
+  /// - issuer_public_key: 颁发者公钥
+  /// ## Returns
+  /// 签名是否有效，程序错误则返回Sm2Error
+  pub fn verify_signature(&self, sm2: &Sm2, issuer_public_key: &Sm2PublicKey) -> Result<bool, Sm2Error> {
+    verify_sm2_signature(sm2, &self.tbs_arrs, &self.signature, issuer_public_key)
+  }
+
+  /// 验证证书是否由给定的颁发者证书签发：名称匹配、颁发者可签发证书且签名有效
+  /// ## Parameters
+  /// - sm2: SM2椭圆曲线参数
+  /// - issuer: 颁发者证书
+  pub fn verify_issued_by(&self, sm2: &Sm2, issuer: &Sm2Certificate) -> Result<bool, Sm2Error> {
+    if self.tbs.issuer != issuer.tbs.subject || !issuer.can_sign_certificate()? {
+      return Ok(false);
+    }
+    self.verify_signature(sm2, &issuer.public_key)
+  }
+}
+
+
+/// 受信任的根证书集合
+#[derive(Clone, Debug, Default)]
+pub struct Sm2TrustStore {
+  roots: Vec<Sm2Certificate>,
+}
+
+
+impl Sm2TrustStore {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// 添加根证书，根证书本身视为可信，不验证其签名
+  pub fn add_root(&mut self, root: Sm2Certificate) {
+    self.roots.push(root);
+  }
+
+  /// 根证书
+  pub fn roots(&self) -> &[Sm2Certificate] {
+    &self.roots
+  }
+
+  /// 验证证书链：从终端证书出发，经中间证书逐级验证签名直到某个根证书
+  /// ## Parameters
+  /// - sm2: SM2椭圆曲线参数
+  /// - leaf: 终端证书
+  /// - intermediates: 中间证书，顺序任意
+  /// - time: 验证时间，通常为`SystemTime::now()`
+  /// ## Returns
+  /// 从终端证书到根证书的完整证书链；证书过期时返回CertificateExpired，
+  /// 无法构建到根证书的链时返回UntrustedCertificate，证书自身不合规时返回InvalidCertificate
+  pub fn verify_chain(
+    &self, sm2: &Sm2, leaf: &Sm2Certificate, intermediates: &[Sm2Certificate], time: SystemTime,
+  ) -> Result<Vec<Sm2Certificate>, Sm2Error> {
+    let mut chain = vec![leaf.clone()];
+
+    for depth in 0..MAX_CHAIN_DEPTH {
+      let current = &chain[depth];
+      if !current.is_valid_at(time) {
+        return Err(Sm2Error::CertificateExpired);
+      }
+      if current.has_unknown_critical_extension() {
+        return Err(Sm2Error::InvalidCertificate);
+      }
+
+      for root in &self.roots {
+        if current.verify_issued_by(sm2, root)? && Self::path_len_allows(root, depth)? {
+          if !root.is_valid_at(time) {
+            return Err(Sm2Error::CertificateExpired);
+          }
+          // 终端证书本身即为根证书时不重复加入
+          if root.der_arrs != current.der_arrs {
+            chain.push(root.clone());
+          }
+          return Ok(chain);
+        }
+      }
+
+      let mut issuer = None;
+      for intermediate in intermediates {
+        if intermediate.der_arrs != current.der_arrs && current.verify_issued_by(sm2, intermediate)?
+          && Self::path_len_allows(intermediate, depth)? {
+          issuer = Some(intermediate.clone());
+          break;
+        }
+      }
+      chain.push(issuer.ok_or(Sm2Error::UntrustedCertificate)?);
+    }
+
+    Err(Sm2Error::UntrustedCertificate)
+  }
+
+  /// pathLenConstraint限制其下方中间证书的数量
+  fn path_len_allows(issuer: &Sm2Certificate, depth: usize) -> Result<bool, Sm2Error> {
+    Ok(issuer.basic_constraints()?.path_len_constraint.is_none_or(|path_len| depth <= path_len as usize))
+  }
+}
+
+
+
+/// 密钥用途，取值为keyUsage中对应的比特位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sm2KeyUsage {
+  DigitalSignature = 0,
+  NonRepudiation = 1,
+  KeyEncipherment = 2,
+  DataEncipherment = 3,
+  KeyAgreement = 4,
+  KeyCertSign = 5,
+  CrlSign = 6,
+}
+
+
+const KEY_USAGES: [Sm2KeyUsage; 7] = [
+  Sm2KeyUsage::DigitalSignature, Sm2KeyUsage::NonRepudiation, Sm2KeyUsage::KeyEncipherment,
+  Sm2KeyUsage::DataEncipherment, Sm2KeyUsage::KeyAgreement, Sm2KeyUsage::KeyCertSign, Sm2KeyUsage::CrlSign,
+];
+
+
+/// 主体备用名称（GeneralName中常用的几种）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Sm2SubjectAltName {
+  // rfc822Name
+  Email(String),
+  // dNSName
+  Dns(String),
+  // uniformResourceIdentifier
+  Uri(String),
+  // iPAddress
+  Ip(IpAddr),
+}
+
+
+impl Sm2SubjectAltName {
+  /// 编码为GeneralName，均为IMPLICIT标签
+  fn to_general_name(&self) -> Result<Any, Sm2Error> {
+    let (tag_number, value) = match self {
+      | Self::Email(email) => (TagNumber::N1, Ia5String::new(email).map_err(|_| Sm2Error::InvalidData)?.as_bytes().to_vec()),
+      | Self::Dns(dns) => (TagNumber::N2, Ia5String::new(dns).map_err(|_| Sm2Error::InvalidData)?.as_bytes().to_vec()),
+      | Self::Uri(uri) => (TagNumber::N6, Ia5String::new(uri).map_err(|_| Sm2Error::InvalidData)?.as_bytes().to_vec()),
+      | Self::Ip(IpAddr::V4(ip)) => (TagNumber::N7, ip.octets().to_vec()),
+      | Self::Ip(IpAddr::V6(ip)) => (TagNumber::N7, ip.octets().to_vec()),
+    };
+    Any::new(Tag::ContextSpecific { constructed: false, number: tag_number }, value).map_err(|_| Sm2Error::Asn1Error)
+  }
+
+  /// 解析GeneralName，忽略不支持的类型
+  fn from_general_name(general_name: &Any) -> Option<Self> {
+    let value = general_name.value();
+    match general_name.tag() {
+      | Tag::ContextSpecific { constructed: false, number } => match number {
+        | TagNumber::N1 => Some(Self::Email(String::from_utf8_lossy(value).to_string())),
+        | TagNumber::N2 => Some(Self::Dns(String::from_utf8_lossy(value).to_string())),
+        | TagNumber::N6 => Some(Self::Uri(String::from_utf8_lossy(value).to_string())),
+        | TagNumber::N7 => match value.len() {
+          | 4 => Some(Self::Ip(IpAddr::from(<[u8; 4]>::try_from(value).ok()?))),
+          | 16 => Some(Self::Ip(IpAddr::from(<[u8; 16]>::try_from(value).ok()?))),
+          | _ => None,
+        },
+        | _ => None,
+      },
+      | _ => None,
+    }
+  }
+}
+
+
+/// 证书及证书请求的参数
+/// - subject: 主体名称，按顺序给出属性简称（`C`、`ST`、`L`、`O`、`OU`、`CN`、`serialNumber`、`emailAddress`）
+///   或点分格式的OID，以及对应的值
+/// - serial_number: 大端字节序的证书序列号，缺省时由Sm2的随机数发生器生成
+/// - not_before: 有效期起始时间
+/// - not_after: 有效期截止时间
+/// - subject_alt_names: 主体备用名称
+/// - key_usages: 密钥用途，为空时不添加keyUsage扩展
+/// - is_ca: 是否为CA证书
+/// - path_len: CA证书的pathLenConstraint
+#[derive(Debug, Clone)]
+pub struct Sm2CertificateParams {
+  pub subject: Vec<(String, String)>,
+  pub serial_number: Option<Vec<u8>>,
+  pub not_before: SystemTime,
+  pub not_after: SystemTime,
+  pub subject_alt_names: Vec<Sm2SubjectAltName>,
+  pub key_usages: Vec<Sm2KeyUsage>,
+  pub is_ca: bool,
+  pub path_len: Option<u32>,
+}
+
+
+impl Default for Sm2CertificateParams {
+  /// 有效期自当前时间起一年
+  fn default() -> Self {
+    let not_before = SystemTime::now();
+    Self {
+      subject: Vec::new(),
+      serial_number: None,
+      not_before,
+      not_after: not_before + Duration::from_secs(365 * 24 * 60 * 60),
+      subject_alt_names: Vec::new(),
+      key_usages: Vec::new(),
+      is_ca: false,
+      path_len: None,
+    }
+  }
+}
+
+
+impl Sm2CertificateParams {
+  /// 以通用名称（CN）构造参数，其余为默认值
+  pub fn new(common_name: &str) -> Self {
+    Self {
+      subject: vec![("CN".to_string(), common_name.to_string())],
+      ..Default::default()
+    }
+  }
+
+  /// 主体名称，每个属性单独作为一个RDN
+  fn to_name(&self) -> Result<NameDer, Sm2Error> {
+    self.subject.iter().map(|(attr_type, attr_value)| {
+      let attr_type = match NAME_ATTRS.iter().find(|(short_name, _)| short_name == attr_type) {
+        | Some((_, oid)) => *oid,
+        | None => ObjectIdentifier::new(attr_type).map_err(|_| Sm2Error::InvalidData)?,
+      };
+      // 国家和序列号使用PrintableString，电子邮件使用IA5String，其余使用UTF8String
+      let attr_value = match NAME_ATTRS.iter().find(|(_, oid)| *oid == attr_type).map(|(short_name, _)| *short_name) {
+        | Some("C") | Some("serialNumber") =>
+          Any::encode_from(&PrintableString::new(attr_value).map_err(|_| Sm2Error::InvalidData)?),
+        | Some("emailAddress") => Any::encode_from(&Ia5String::new(attr_value).map_err(|_| Sm2Error::InvalidData)?),
+        | _ => Any::encode_from(attr_value),
+      }.map_err(|_| Sm2Error::Asn1Error)?;
+
+      SetOfVec::try_from(vec![AttributeTypeAndValueDer { attr_type, attr_value }]).map_err(|_| Sm2Error::Asn1Error)
+    }).collect()
+  }
+
+  /// 扩展项：basicConstraints、keyUsage和subjectAltName
+  fn to_extensions(&self) -> Result<Vec<ExtensionDer>, Sm2Error> {
+    let mut extensions = vec![ExtensionDer {
+      extn_id: OID_BASIC_CONSTRAINTS,
+      critical: true,
+      extn_value: OctetString::new(BasicConstraintsDer {
+        ca: self.is_ca,
+        path_len_constraint: if self.is_ca { self.path_len } else { None },
+      }.to_der().map_err(|_| Sm2Error::Asn1Error)?).map_err(|_| Sm2Error::Asn1Error)?,
+    }];
+
+    if let Some(max_bit) = self.key_usages.iter().map(|usage| *usage as usize).max() {
+      let mut key_usage_arrs = vec![0u8; max_bit / 8 + 1];
+      for usage in &self.key_usages {
+        key_usage_arrs[*usage as usize / 8] |= 0x80 >> (*usage as usize % 8);
+      }
+      let key_usage = BitString::new((7 - max_bit % 8) as u8, key_usage_arrs).map_err(|_| Sm2Error::Asn1Error)?;
+      extensions.push(ExtensionDer {
+        extn_id: OID_KEY_USAGE,
+        critical: true,
+        extn_value: OctetString::new(key_usage.to_der().map_err(|_| Sm2Error::Asn1Error)?)
+          .map_err(|_| Sm2Error::Asn1Error)?,
+      });
+    }
+
+    if !self.subject_alt_names.is_empty() {
+      let general_names = self.subject_alt_names.iter()
+        .map(|alt_name| alt_name.to_general_name())
+        .collect::<Result<Vec<_>, _>>()?;
+      extensions.push(ExtensionDer {
+        extn_id: OID_SUBJECT_ALT_NAME,
+        // 主体名称为空时subjectAltName须为关键扩展（RFC 5280 4.2.1.6）
+        critical: self.subject.is_empty(),
+        extn_value: OctetString::new(general_names.to_der().map_err(|_| Sm2Error::Asn1Error)?)
+          .map_err(|_| Sm2Error::Asn1Error)?,
+      });
+    }
+
+    Ok(extensions)
+  }
+
+  /// 序列号须为正整数，且不超过20字节
+  fn to_serial_number(&self, sm2: &Sm2) -> Result<Int, Sm2Error> {
+    let mut serial_arrs = match &self.serial_number {
+      | Some(serial_number) => {
+        let first_nonzero = serial_number.iter().position(|byt| *byt != 0).ok_or(Sm2Error::InvalidData)?;
+        serial_number[first_nonzero..].to_vec()
+      }
+      | None => {
+        let mut serial_arrs = vec![0u8; 16];
+        sm2.fill_random_bytes(&mut serial_arrs);
+        // 最高位置0保证为正数，次高位置1保证编码长度固定
+        serial_arrs[0] = (serial_arrs[0] & 0x7f) | 0x40;
+        serial_arrs
+      }
+    };
+    if serial_arrs[0] & 0x80 != 0 {
+      serial_arrs.insert(0, 0);
+    }
+    if serial_arrs.len() > 20 {
+      return Err(Sm2Error::InvalidData);
+    }
+    Int::new(&serial_arrs).map_err(|_| Sm2Error::Asn1Error)
+  }
+}
+
+
+/// 解析subjectAltName扩展
+fn parse_subject_alt_names(extension: Option<&ExtensionDer>) -> Result<Vec<Sm2SubjectAltName>, Sm2Error> {
+  let Some(extension) = extension else {
+    return Ok(Vec::new());
+  };
+  let general_names = Vec::<Any>::from_der(extension.extn_value.as_bytes()).map_err(|_| Sm2Error::Asn1Error)?;
+  Ok(general_names.iter().filter_map(Sm2SubjectAltName::from_general_name).collect())
+}
+
+
+/// SM2-with-SM3签名算法标识，参数缺省
+fn sm2_with_sm3_algorithm() -> AlgorithmIdentifierDer {
+  AlgorithmIdentifierDer { algorithm: OID_SM2_WITH_SM3, parameters: None }
+}
+
+
+/// 使用默认用户ID签名，返回DER编码的SM2签名值
+fn sign_sm2_signature(sm2: &Sm2, plain_arrs: &[u8], private_key: &Sm2PrivateKey) -> Result<Vec<u8>, Sm2Error> {
+  let sign_hex = sm2.sign_bytes(plain_arrs, private_key, false, true, None, None)?;
+  Sm2SignatureDer {
+    r: Uint::new(&hex_anly_arrs(&sign_hex[..64])).map_err(|_| Sm2Error::Asn1Error)?,
+    s: Uint::new(&hex_anly_arrs(&sign_hex[64..])).map_err(|_| Sm2Error::Asn1Error)?,
+  }.to_der().map_err(|_| Sm2Error::Asn1Error)
+}
+
+
+impl Sm2Certificate {
+  /// 生成自签名证书，颁发者与主体相同
+  /// ## Parameters
+  /// - sm2: SM2椭圆曲线参数
+  /// - params: 证书参数
+  /// - private_key: 主体私钥，同时用于签名
+  pub fn self_signed(
+    sm2: &Sm2, params: &Sm2CertificateParams, private_key: &Sm2PrivateKey,
+  ) -> Result<Self, Sm2Error> {
+    if params.not_before > params.not_after {
+      return Err(Sm2Error::InvalidData);
+    }
+    let name = params.to_name()?;
+    let tbs = TbsCertificateDer {
+      version: 2,
+      serial_number: params.to_serial_number(sm2)?,
+      signature: sm2_with_sm3_algorithm(),
+      issuer: name.clone(),
+      validity: ValidityDer {
+        not_before: TimeDer::from_system_time(params.not_before)?,
+        not_after: TimeDer::from_system_time(params.not_after)?,
+      },
+      subject: name,
+      subject_public_key_info: SubjectPublicKeyInfoDer::from_sm2_public_key(private_key.public_key())?,
+      issuer_unique_id: None,
+      subject_unique_id: None,
+      extensions: Some(params.to_extensions()?),
+    };
+    let tbs_arrs = tbs.to_der().map_err(|_| Sm2Error::Asn1Error)?;
+    let signature = sign_sm2_signature(sm2, &tbs_arrs, private_key)?;
+
+    let der_arrs = CertificateDer {
+      tbs_certificate: Any::from_der(&tbs_arrs).map_err(|_| Sm2Error::Asn1Error)?,
+      signature_algorithm: sm2_with_sm3_algorithm(),
+      signature_value: BitString::from_bytes(&signature).map_err(|_| Sm2Error::Asn1Error)?,
+    }.to_der().map_err(|_| Sm2Error::Asn1Error)?;
+    Self::from_der(sm2, &der_arrs)
+  }
+
+  /// 主体备用名称
+  pub fn subject_alt_names(&self) -> Result<Vec<Sm2SubjectAltName>, Sm2Error> {
+    parse_subject_alt_names(self.extension(OID_SUBJECT_ALT_NAME))
+  }
+
+  /// 密钥用途，未包含keyUsage扩展时返回空
+  pub fn key_usages(&self) -> Result<Vec<Sm2KeyUsage>, Sm2Error> {
+    let Some(extension) = self.extension(OID_KEY_USAGE) else {
+      return Ok(Vec::new());
+    };
+    let key_usage = BitString::from_der(extension.extn_value.as_bytes()).map_err(|_| Sm2Error::Asn1Error)?;
+    let key_usage_arrs = key_usage.raw_bytes();
+    Ok(KEY_USAGES.iter().filter(|usage| {
+      let bit = **usage as usize;
+      key_usage_arrs.get(bit / 8).is_some_and(|byt| byt & (0x80 >> (bit % 8)) != 0)
+    }).copied().collect())
+  }
+}
+
+
+/// PKCS#9 extensionRequest
+const OID_EXTENSION_REQUEST: ObjectIdentifier = ObjectIdentifier::new_unwrap("1.2.840.113549.1.9.14");
+
+const PEM_LABEL_CERTIFICATE_REQUEST: &str = "CERTIFICATE REQUEST";
+
+
+/// Attribute ::= SEQUENCE { type OBJECT IDENTIFIER, values SET OF ANY }
+#[derive(Clone, Debug, Eq, PartialEq, Sequence, ValueOrd)]
+struct AttributeDer {
+  attr_type: ObjectIdentifier,
+  attr_values: SetOfVec<Any>,
+}
+
+
+/// CertificationRequestInfo
+#[derive(Clone, Debug, Eq, PartialEq, Sequence)]
+struct CertificationRequestInfoDer {
+  version: u8,
+  subject: NameDer,
+  subject_public_key_info: SubjectPublicKeyInfoDer,
+  #[asn1(context_specific = "0", tag_mode = "IMPLICIT")]
+  attributes: SetOfVec<AttributeDer>,
+}
+
+
+/// CertificationRequest，CertificationRequestInfo保留原始编码以便验签
+#[derive(Clone, Debug, Eq, PartialEq, Sequence)]
+struct CertificationRequestDer {
+  certification_request_info: Any,
+  signature_algorithm: AlgorithmIdentifierDer,
+  signature: BitString,
+}
+
+
+/// SM2-with-SM3签名的PKCS#10证书请求
+/// - der_arrs: 证书请求的DER编码
+/// - info_arrs: CertificationRequestInfo的DER编码，即签名的对象
+/// - info: 解析后的CertificationRequestInfo
+/// - signature: DER编码的签名值
+/// - public_key: 主体公钥
+#[derive(Clone, Debug)]
+pub struct Sm2CertificateRequest {
+  der_arrs: Vec<u8>,
+  info_arrs: Vec<u8>,
+  info: CertificationRequestInfoDer,
+  signature: Vec<u8>,
+  public_key: Sm2PublicKey,
+}
+
+
+impl Sm2CertificateRequest {
+  /// 生成证书请求，请求的扩展项（basicConstraints、keyUsage和subjectAltName）放入extensionRequest属性，
+  /// 有效期和序列号由CA决定，此处忽略
+  /// ## Parameters
+  /// - sm2: SM2椭圆曲线参数
+  /// - params: 证书参数
+  /// - private_key: 主体私钥，用于签名
+  pub fn new(sm2: &Sm2, params: &Sm2CertificateParams, private_key: &Sm2PrivateKey) -> Result<Self, Sm2Error> {
+    let extensions = Any::encode_from(&params.to_extensions()?).map_err(|_| Sm2Error::Asn1Error)?;
+    let info = CertificationRequestInfoDer {
+      version: 0,
+      subject: params.to_name()?,
+      subject_public_key_info: SubjectPublicKeyInfoDer::from_sm2_public_key(private_key.public_key())?,
+      attributes: SetOfVec::try_from(vec![AttributeDer {
+        attr_type: OID_EXTENSION_REQUEST,
+        attr_values: SetOfVec::try_from(vec![extensions]).map_err(|_| Sm2Error::Asn1Error)?,
+      }]).map_err(|_| Sm2Error::Asn1Error)?,
+    };
+    let info_arrs = info.to_der().map_err(|_| Sm2Error::Asn1Error)?;
+    let signature = sign_sm2_signature(sm2, &info_arrs, private_key)?;
+
+    let der_arrs = CertificationRequestDer {
+      certification_request_info: Any::from_der(&info_arrs).map_err(|_| Sm2Error::Asn1Error)?,
+      signature_algorithm: sm2_with_sm3_algorithm(),
+      signature: BitString::from_bytes(&signature).map_err(|_| Sm2Error::Asn1Error)?,
+    }.to_der().map_err(|_| Sm2Error::Asn1Error)?;
+    Self::from_der(sm2, &der_arrs)
+  }
+
+  /// 解析DER编码的证书请求
+  /// ## Parameters
+  /// - sm2: SM2椭圆曲线参数
+  /// - der_arrs: DER编码的证书请求
+  pub fn from_der(sm2: &Sm2, der_arrs: &[u8]) -> Result<Self, Sm2Error> {
+    let csr = CertificationRequestDer::from_der(der_arrs).map_err(|_| Sm2Error::Asn1Error)?;
+    let info = csr.certification_request_info.decode_as::<CertificationRequestInfoDer>()
+      .map_err(|_| Sm2Error::Asn1Error)?;
+    check_sm2_with_sm3(&csr.signature_algorithm)?;
+
+    let public_key = info.subject_public_key_info.to_sm2_public_key(sm2)?;
+    Ok(Self {
+      der_arrs: der_arrs.to_vec(),
+      info_arrs: csr.certification_request_info.to_der().map_err(|_| Sm2Error::Asn1Error)?,
+      signature: csr.signature.as_bytes().ok_or(Sm2Error::Asn1Error)?.to_vec(),
+      info,
+      public_key,
+    })
+  }
+
+  /// 解析PEM编码的证书请求，标签为`CERTIFICATE REQUEST`
+  /// ## Parameters
+  /// - sm2: SM2椭圆曲线参数
+  /// - pem_talks: PEM编码的证书请求
+  pub fn from_pem(sm2: &Sm2, pem_talks: &str) -> Result<Self, Sm2Error> {
+    Self::from_der(sm2, &pem_anly_der(pem_talks, &[PEM_LABEL_CERTIFICATE_REQUEST])?)
+  }
+
+  /// 证书请求的DER编码
+  pub fn to_der(&self) -> &[u8] {
+    &self.der_arrs
+  }
+
+  /// 证书请求的PEM编码
+  pub fn to_pem(&self) -> Result<String, Sm2Error> {
+    der_to_pem(PEM_LABEL_CERTIFICATE_REQUEST, &self.der_arrs)
+  }
+
+  /// 主体名称
+  pub fn subject(&self) -> String {
+    name_to_string(&self.info.subject)
+  }
+
+  /// 主体公钥
+  pub fn public_key(&self) -> &Sm2PublicKey {
+    &self.public_key
+  }
+
+  /// extensionRequest属性中请求的扩展项
+  fn requested_extensions(&self) -> Result<Vec<ExtensionDer>, Sm2Error> {
+    match self.info.attributes.iter().find(|attr| attr.attr_type == OID_EXTENSION_REQUEST) {
+      | Some(attr) => attr.attr_values.iter().next().ok_or(Sm2Error::Asn1Error)?
+        .decode_as::<Vec<ExtensionDer>>().map_err(|_| Sm2Error::Asn1Error),
+      | None => Ok(Vec::new()),
+    }
+  }
+
+  /// 请求的主体备用名称
+  pub fn subject_alt_names(&self) -> Result<Vec<Sm2SubjectAltName>, Sm2Error> {
+    let extensions = self.requested_extensions()?;
+    parse_subject_alt_names(extensions.iter().find(|extn| extn.extn_id == OID_SUBJECT_ALT_NAME))
+  }
+
+  /// 使用请求中的公钥验证签名，用户ID为默认的`1234567812345678`
+  pub fn verify(&self, sm2: &Sm2) -> Result<bool, Sm2Error> {
+    verify_sm2_signature(sm2, &self.info_arrs, &self.signature, &self.public_key)
+  }
+}
@@ -1,3 +1,7 @@
+use crate::secret::Secret;
+use crate::sm3::sm3::Sm3Error;
+
+
 /// 字节数组异或
 fn xor_in_bytes(byts1: &[u8], byts2: &[u8]) -> Vec<u8> {
   byts1.iter().zip(byts2.iter()).map(|(&byti1, &byti2)| byti1 ^ byti2).collect()
@@ -18,13 +22,14 @@ fn p1(dial: u32) -> u32 {
 
 /// SM3填充压缩数据为64字节整数倍[元数据，0x80，零填充，原始长度填充]
 /// ## Parameters
-/// - byt_arrs: 待填充的字节数组
-fn sm3_pad(byt_arrs: &[u8]) -> Vec<u8> {
+/// - byt_arrs: 待填充的字节数组，即消息末尾不足一个分组的部分
+/// - bytl: 消息的总字节数
+fn sm3_pad(byt_arrs: &[u8], bytl: u64) -> Vec<u8> {
   // m = [...array, 0x80, ...kArr, ...lenArr]
   // ...array
   let mut reap = byt_arrs.to_vec();
 
-  let bytl = (reap.len() as u64) * 8;
+  let bytl = bytl.wrapping_mul(8);
   // 0x80
   reap.push(0x80);
 
@@ -35,25 +40,12 @@ fn sm3_pad(byt_arrs: &[u8]) -> Vec<u8> {
   //   0x80: 1b
   //   ...kArr: k >= 448 ? 512 - (k % 448) - 1 : 448 - k - 1
   //   ...lenArr: 64b
-  // 先补充到56byte
-  let mut kl = 56usize.wrapping_sub(reap.len() % 64);
-  if reap.len() % 64 > 56 {
-    // 新补一块64byte
-    kl += 64;
-  }
+  // 先补充到56byte，超过56byte时新补一块64byte
+  let kl = (56 + 64 - reap.len() % 64) % 64;
   // kArr
   reap.extend(vec![0u8; kl]);
   // lenArr
-  reap.extend_from_slice(&[
-    (bytl >> 56) as u8,
-    (bytl >> 48) as u8,
-    (bytl >> 40) as u8,
-    (bytl >> 32) as u8,
-    (bytl >> 24) as u8,
-    (bytl >> 16) as u8,
-    (bytl >> 8) as u8,
-    bytl as u8,
-  ]);
+  reap.extend_from_slice(&bytl.to_be_bytes());
   reap
 }
 
@@ -71,86 +63,156 @@ fn data_view_get_uint_32(byt4: &[u8], is_be: bool) -> u32 {
 }
 
 
-/// SM3压缩函数
+/// SM3压缩函数CF，以一个64字节的分组更新字寄存器
 /// ## Parameters
-/// - byt_arrs: 待压缩的字节数组
-pub fn sm3_digest(byt_arrs: &[u8]) -> Vec<u8> {
-  let mut sm3_v: [u32; 8] = [
-    0x7380166f, 0x4914b2b9, 0x172442d7, 0xda8a0600, 0xa96f30bc, 0x163138aa, 0xe38dee4d, 0xb0fb0e4e
-  ];
-
-  let byt_arrs = sm3_pad(byt_arrs);
-  for byt_arri in byt_arrs.chunks_exact(64) {
-    let mut w = [0u32; 68];
-    let mut m = [0u32; 64];
-
-    // 将消息分组B划分为16个字W0, W1, \dots, W15
-    for i in 0..16 {
-      w[i] = data_view_get_uint_32(&byt_arri[i * 4..(i + 1) * 4], true);
+/// - sm3_v: 当前的杂凑中间值V
+/// - byt_arri: 64字节的消息分组B
+fn sm3_compress(sm3_v: &mut [u32; 8], byt_arri: &[u8]) {
+  let mut w = [0u32; 68];
+  let mut m = [0u32; 64];
+
+  // 将消息分组B划分为16个字W0, W1, \dots, W15
+  for i in 0..16 {
+    w[i] = data_view_get_uint_32(&byt_arri[i * 4..(i + 1) * 4], true);
+  }
+
+  // W16 -> W67：W[i] <- P1(W[i−16] xor W[i−9] xor (W[i−3] <<< 15)) xor (W[i−13] <<< 7) xor W[i−6]
+  for i in 16..68 {
+    w[i] = p1(w[i - 16] ^ w[i - 9] ^ w[i - 3].rotate_left(15))
+      ^ w[i - 13].rotate_left(7)
+      ^ w[i - 6];
+  }
+
+  // W′0 ～ W′63：W′[i] = W[i] xor W[i+4]
+  for i in 0..64 {
+    m[i] = w[i] ^ w[i + 4];
+  }
+
+  // 字寄存器
+  let mut a = sm3_v[0];
+  let mut b = sm3_v[1];
+  let mut c = sm3_v[2];
+  let mut d = sm3_v[3];
+  let mut e = sm3_v[4];
+  let mut f = sm3_v[5];
+  let mut g = sm3_v[6];
+  let mut h = sm3_v[7];
+
+  for i in 0..64 {
+    let t: u32 = if i <= 15 { 0x79cc4519 } else { 0x7a879d8a };
+    // SS1 = rotl(rotl(A, 12) + E + rotl(T, i), 7)
+    let ss1 = a.rotate_left(12).wrapping_add(e).wrapping_add(t.rotate_left(i as u32))
+      .rotate_left(7);
+    // SS2 = SS1 ^ rotl(A, 12)
+    let ss2 = ss1 ^ a.rotate_left(12);
+    // TT1 = (i >= 0 && i <= 15 ? ((A ^ B) ^ C) : (((A & B) | (A & C)) | (B & C))) + D + SS2 + M[i]
+    let tt1 = if i <= 15 { a ^ b ^ c } else { (a & b) | (a & c) | (b & c) }
+      .wrapping_add(d).wrapping_add(ss2).wrapping_add(m[i]);
+    // TT2 = (i >= 0 && i <= 15 ? ((E ^ F) ^ G) : ((E & F) | ((~E) & G))) + H + SS1 + W[i]
+    let tt2 = if i <= 15 { e ^ f ^ g } else { (e & f) | ((!e) & g) }
+      .wrapping_add(h).wrapping_add(ss1).wrapping_add(w[i]);
+
+    d = c;
+    c = b.rotate_left(9);
+    b = a;
+    a = tt1;
+    h = g;
+    g = f.rotate_left(19);
+    f = e;
+    e = p0(tt2);
+  }
+
+  sm3_v[0] ^= a;
+  sm3_v[1] ^= b;
+  sm3_v[2] ^= c;
+  sm3_v[3] ^= d;
+  sm3_v[4] ^= e;
+  sm3_v[5] ^= f;
+  sm3_v[6] ^= g;
+  sm3_v[7] ^= h;
+}
+
+
+/// 增量计算的SM3杂凑，可分多次输入消息，适用于大文件等无法一次读入内存的数据
+/// - sm3_v: 杂凑中间值V
+/// - reap: 尚未凑满一个分组的消息
+/// - bytl: 已输入消息的总字节数
+#[derive(Clone)]
+pub struct Sm3Hasher {
+  sm3_v: [u32; 8],
+  reap: Secret<Vec<u8>>,
+  bytl: u64,
+}
+
+
+impl Default for Sm3Hasher {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+
+impl Sm3Hasher {
+  pub fn new() -> Self {
+    Self {
+      sm3_v: [
+        0x7380166f, 0x4914b2b9, 0x172442d7, 0xda8a0600, 0xa96f30bc, 0x163138aa, 0xe38dee4d, 0xb0fb0e4e
+      ],
+      reap: Secret(Vec::with_capacity(64)),
+      bytl: 0,
     }
+  }
 
-    // W16 -> W67：W[i] <- P1(W[i−16] xor W[i−9] xor (W[i−3] <<< 15)) xor (W[i−13] <<< 7) xor W[i−6]
-    for i in 16..68 {
-      w[i] = p1(w[i - 16] ^ w[i - 9] ^ w[i - 3].rotate_left(15))
-        ^ w[i - 13].rotate_left(7)
-        ^ w[i - 6];
+  /// 输入一段消息
+  /// ## Parameters
+  /// - byt_arrs: 消息片段
+  pub fn update(&mut self, mut byt_arrs: &[u8]) {
+    self.bytl = self.bytl.wrapping_add(byt_arrs.len() as u64);
+
+    // 先补满缓存中的分组
+    if !self.reap.is_empty() {
+      let takel = (64 - self.reap.len()).min(byt_arrs.len());
+      self.reap.extend_from_slice(&byt_arrs[..takel]);
+      byt_arrs = &byt_arrs[takel..];
+      if self.reap.len() < 64 {
+        return;
+      }
+      sm3_compress(&mut self.sm3_v, &self.reap);
+      self.reap.clear();
     }
 
-    // W′0 ～ W′63：W′[i] = W[i] xor W[i+4]
-    for i in 0..64 {
-      m[i] = w[i] ^ w[i + 4];
+    let mut byt_chunks = byt_arrs.chunks_exact(64);
+    for byt_arri in &mut byt_chunks {
+      sm3_compress(&mut self.sm3_v, byt_arri);
     }
+    self.reap.extend_from_slice(byt_chunks.remainder());
+  }
 
-    // 字寄存器
-    let mut a = sm3_v[0];
-    let mut b = sm3_v[1];
-    let mut c = sm3_v[2];
-    let mut d = sm3_v[3];
-    let mut e = sm3_v[4];
-    let mut f = sm3_v[5];
-    let mut g = sm3_v[6];
-    let mut h = sm3_v[7];
-
-    for i in 0..64 {
-      let t: u32 = if i <= 15 { 0x79cc4519 } else { 0x7a879d8a };
-      // SS1 = rotl(rotl(A, 12) + E + rotl(T, i), 7)
-      let ss1 = a.rotate_left(12).wrapping_add(e).wrapping_add(t.rotate_left(i as u32))
-        .rotate_left(7);
-      // SS2 = SS1 ^ rotl(A, 12)
-      let ss2 = ss1 ^ a.rotate_left(12);
-      // TT1 = (i >= 0 && i <= 15 ? ((A ^ B) ^ C) : (((A & B) | (A & C)) | (B & C))) + D + SS2 + M[i]
-      let tt1 = if i <= 15 { a ^ b ^ c } else { (a & b) | (a & c) | (b & c) }
-        .wrapping_add(d).wrapping_add(ss2).wrapping_add(m[i]);
-      // TT2 = (i >= 0 && i <= 15 ? ((E ^ F) ^ G) : ((E & F) | ((~E) & G))) + H + SS1 + W[i]
-      let tt2 = if i <= 15 { e ^ f ^ g } else { (e & f) | ((!e) & g) }
-        .wrapping_add(h).wrapping_add(ss1).wrapping_add(w[i]);
-
-      d = c;
-      c = b.rotate_left(9);
-      b = a;
-      a = tt1;
-      h = g;
-      g = f.rotate_left(19);
-      f = e;
-      e = p0(tt2);
+  /// 结束输入，返回32字节的杂凑值
+  pub fn finalize(mut self) -> Vec<u8> {
+    // 输入可能是HMAC密钥或共享点等秘密值，填充后的副本同样需要擦除
+    let byt_arrs = Secret(sm3_pad(&self.reap, self.bytl));
+    for byt_arri in byt_arrs.chunks_exact(64) {
+      sm3_compress(&mut self.sm3_v, byt_arri);
     }
 
-    sm3_v[0] ^= a;
-    sm3_v[1] ^= b;
-    sm3_v[2] ^= c;
-    sm3_v[3] ^= d;
-    sm3_v[4] ^= e;
-    sm3_v[5] ^= f;
-    sm3_v[6] ^= g;
-    sm3_v[7] ^= h;
+    // 转回u8
+    let mut reap = Vec::with_capacity(32);
+    for sm3_vi in self.sm3_v.iter() {
+      reap.extend_from_slice(&sm3_vi.to_be_bytes());
+    }
+    reap
   }
+}
 
-  // 转回u8
-  let mut reap = Vec::with_capacity(32);
-  for sm3_vi in sm3_v.iter() {
-    reap.extend_from_slice(&sm3_vi.to_be_bytes());
-  }
-  reap
+
+/// SM3杂凑
+/// ## Parameters
+/// - byt_arrs: 待杂凑的字节数组
+pub fn sm3_digest(byt_arrs: &[u8]) -> Vec<u8> {
+  let mut sm3_hasher = Sm3Hasher::new();
+  sm3_hasher.update(byt_arrs);
+  sm3_hasher.finalize()
 }
 
 
@@ -162,20 +224,100 @@ pub fn sm3_hmac(sm3_k: &[u8], val: &[u8]) -> Vec<u8> {
   const BLOCK: usize = 64;
 
   // 密钥填充
-  let mut sm3_k = sm3_k.to_vec();
+  let mut sm3_k = Secret(sm3_k.to_vec());
   if sm3_k.len() > BLOCK {
-    sm3_k = sm3_digest(&sm3_k);
+    sm3_k = Secret(sm3_digest(&sm3_k));
   }
   if sm3_k.len() < BLOCK {
-    sm3_k.extend(vec![0; BLOCK - sm3_k.len()]);
+    let padl = BLOCK - sm3_k.len();
+    sm3_k.extend(vec![0; padl]);
   }
 
   let i_pad = vec![0x36; BLOCK];
   let o_pad = vec![0x5c; BLOCK];
 
-  let i_pad_k = xor_in_bytes(&sm3_k, &i_pad);
-  let o_pad_k = xor_in_bytes(&sm3_k, &o_pad);
+  let i_pad_k = Secret(xor_in_bytes(&sm3_k, &i_pad));
+  let o_pad_k = Secret(xor_in_bytes(&sm3_k, &o_pad));
+
+  let inner = sm3_digest(&Secret([&i_pad_k[..], val].concat()));
+  sm3_digest(&Secret([&o_pad_k[..], &inner[..]].concat()))
+}
+
+
+/// 基于HMAC-SM3的PBKDF2口令派生密钥算法（RFC 8018）
+/// ## Parameters
+/// - password: 口令
+/// - salt: 盐值
+/// - iter_cnt: 迭代次数，至少为1
+/// - dk_len: 派生密钥的字节长度
+pub fn sm3_pbkdf2(password: &[u8], salt: &[u8], iter_cnt: u32, dk_len: usize) -> Vec<u8> {
+  let mut reap = Vec::with_capacity(dk_len);
+  let mut blk_idx: u32 = 1;
+
+  while reap.len() < dk_len {
+    // U1 = PRF(P, S || INT(i))，Uj = PRF(P, Uj-1)，Ti = U1 xor U2 xor ... xor Uc
+    let mut u = Secret(sm3_hmac(password, &[salt, &blk_idx.to_be_bytes()].concat()));
+    let mut t = Secret(u.to_vec());
+    for _ in 1..iter_cnt {
+      u = Secret(sm3_hmac(password, &u));
+      t = Secret(xor_in_bytes(&t, &u));
+    }
+
+    let takel = (dk_len - reap.len()).min(t.len());
+    reap.extend_from_slice(&t[..takel]);
+    blk_idx += 1;
+  }
+  reap
+}
+
+
+/// 超过255字节的域分隔标签先按RFC 9380第5.3.3节杂凑为32字节
+const XMD_OVERSIZE_DST_PREFIX: &[u8] = b"H2C-OVERSIZE-DST-";
+
+
+/// 以SM3为杂凑函数的expand_message_xmd（RFC 9380第5.3.1节），用于hash-to-curve和hash-to-field
+/// ## Parameters
+/// - msg: 消息
+/// - dst: 域分隔标签，不能为空，超过255字节时先杂凑
+/// - len_in_bytes: 输出的字节数，至多255 * 32字节
+/// ## Returns
+/// 伪随机字节数组；域分隔标签为空返回Sm3Error::InvalidDst，输出过长返回Sm3Error::RequestTooLarge
+pub fn sm3_expand_message_xmd(msg: &[u8], dst: &[u8], len_in_bytes: usize) -> Result<Vec<u8>, Sm3Error> {
+  const B_IN_BYTES: usize = 32;
+  const S_IN_BYTES: usize = 64;
 
-  let inner = sm3_digest(&[i_pad_k, val.to_vec()].concat());
-  sm3_digest(&[o_pad_k, inner].concat())
+  if dst.is_empty() {
+    return Err(Sm3Error::InvalidDst);
+  }
+  let ell = len_in_bytes.div_ceil(B_IN_BYTES);
+  if ell > 255 {
+    return Err(Sm3Error::RequestTooLarge);
+  }
+  let dst = if dst.len() > 255 {
+    sm3_digest(&[XMD_OVERSIZE_DST_PREFIX, dst].concat())
+  } else {
+    dst.to_vec()
+  };
+  // DST_prime = DST || I2OSP(len(DST), 1)
+  let dst_prime = [&dst[..], &[dst.len() as u8]].concat();
+
+  // b_0 = H(Z_pad || msg || l_i_b_str || I2OSP(0, 1) || DST_prime)
+  let mut sm3_hasher = Sm3Hasher::new();
+  sm3_hasher.update(&[0u8; S_IN_BYTES]);
+  sm3_hasher.update(msg);
+  sm3_hasher.update(&(len_in_bytes as u16).to_be_bytes());
+  sm3_hasher.update(&[0]);
+  sm3_hasher.update(&dst_prime);
+  let b0 = Secret(sm3_hasher.finalize());
+
+  // b_1 = H(b_0 || I2OSP(1, 1) || DST_prime)，b_i = H(strxor(b_0, b_(i - 1)) || I2OSP(i, 1) || DST_prime)
+  let mut reap = Vec::with_capacity(ell * B_IN_BYTES);
+  let mut bi = Secret(vec![0u8; B_IN_BYTES]);
+  for i in 1..=ell {
+    let b0_xor_bi = Secret(xor_in_bytes(&b0, &bi));
+    bi = Secret(sm3_digest(&Secret([&b0_xor_bi[..], &[i as u8], &dst_prime].concat())));
+    reap.extend_from_slice(&bi);
+  }
+  reap.truncate(len_in_bytes);
+  Ok(reap)
 }
@@ -0,0 +1,279 @@
+use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore};
+use crate::secret::Secret;
+use crate::sm3::achieve::*;
+use crate::sm3::sm3::Sm3Error;
+
+
+/// 种子长度seedlen = 440比特（SP 800-90A中输出256比特杂凑函数的取值）
+const SEED_LEN: usize = 55;
+/// 实例化与重播种时读取的熵输入长度，对应256比特安全强度
+const ENTROPY_LEN: usize = 32;
+/// 实例化时读取的随机数nonce长度，不少于安全强度的一半
+const NONCE_LEN: usize = 16;
+/// 单次生成的最大字节数（2^19比特）
+pub const MAX_REQUEST_LEN: usize = 1 << 16;
+/// 默认重播种间隔（2^48次生成请求）
+pub const RESEED_INTERVAL: u64 = 1 << 48;
+
+
+/// 将w按大端整数加到v上，结果模2^(8 * v.len())
+fn add_in_bytes(v: &mut [u8], w: &[u8]) {
+  let mut carry = 0u16;
+  let mut w_iter = w.iter().rev();
+  for vi in v.iter_mut().rev() {
+    let sum = *vi as u16 + *w_iter.next().unwrap_or(&0) as u16 + carry;
+    *vi = sum as u8;
+    carry = sum >> 8;
+  }
+}
+
+
+/// Hash_df派生函数
+/// ## Parameters
+/// - input: 输入串
+/// - bytl: 需要输出的字节数
+fn hash_df(input: &[u8], bytl: usize) -> Vec<u8> {
+  let mut reap = Vec::with_capacity(bytl + 32);
+  let mut counter = 1u8;
+  while reap.len() < bytl {
+    // Hash(counter || no_of_bits_to_return || input_string)
+    reap.extend(sm3_digest(&Secret(
+      [&[counter][..], &((bytl * 8) as u32).to_be_bytes(), input].concat()
+    )));
+    counter = counter.wrapping_add(1);
+  }
+  reap.truncate(bytl);
+  reap
+}
+
+
+/// 基于SM3的Hash_DRBG（NIST SP 800-90A），可直接作为`RngCore`使用，熵源为`CryptoRng`时同时实现`CryptoRng`
+pub struct Sm3HashDrbg<E: RngCore = OsRng> {
+  v: Secret<Vec<u8>>,
+  c: Secret<Vec<u8>>,
+  reseed_counter: u64,
+  reseed_interval: u64,
+  prediction_resistance: bool,
+  entropy_source: E,
+}
+
+
+/// 基于HMAC-SM3的HMAC_DRBG（NIST SP 800-90A），可直接作为`RngCore`使用，熵源为`CryptoRng`时同时实现`CryptoRng`
+pub struct Sm3HmacDrbg<E: RngCore = OsRng> {
+  k: Secret<Vec<u8>>,
+  v: Secret<Vec<u8>>,
+  reseed_counter: u64,
+  reseed_interval: u64,
+  prediction_resistance: bool,
+  entropy_source: E,
+}
+
+
+impl<E: RngCore> Sm3HashDrbg<E> {
+  fn instantiate_algorithm(&mut self, seed_material: &[u8]) {
+    self.v = Secret(hash_df(seed_material, SEED_LEN));
+    self.c = Secret(hash_df(&Secret([&[0x00][..], &self.v].concat()), SEED_LEN));
+    self.reseed_counter = 1;
+  }
+
+  fn reseed_algorithm(&mut self, entropy_input: &[u8], additional_input: &[u8]) {
+    let seed_material = Secret([&[0x01][..], &self.v, entropy_input, additional_input].concat());
+    self.instantiate_algorithm(&seed_material);
+  }
+
+  fn generate_algorithm(&mut self, out: &mut [u8], additional_input: &[u8]) {
+    if !additional_input.is_empty() {
+      let w = sm3_digest(&Secret([&[0x02][..], &self.v, additional_input].concat()));
+      add_in_bytes(&mut self.v, &w);
+    }
+
+    // Hashgen：依次杂凑V, V + 1, V + 2, ...
+    let mut data = Secret(self.v.to_vec());
+    for outi in out.chunks_mut(32) {
+      let wi = Secret(sm3_digest(&data));
+      outi.copy_from_slice(&wi[..outi.len()]);
+      add_in_bytes(&mut data, &[0x01]);
+    }
+
+    // V = (V + H + C + reseed_counter) mod 2^seedlen
+    let h = sm3_digest(&Secret([&[0x03][..], &self.v].concat()));
+    let c = Secret(self.c.to_vec());
+    add_in_bytes(&mut self.v, &h);
+    add_in_bytes(&mut self.v, &c);
+    add_in_bytes(&mut self.v, &self.reseed_counter.to_be_bytes());
+    self.reseed_counter += 1;
+  }
+}
+
+
+impl<E: RngCore> Sm3HmacDrbg<E> {
+  /// HMAC_DRBG的Update函数
+  fn update(&mut self, provided_data: &[u8]) {
+    for sep in [0x00, 0x01] {
+      self.k = Secret(sm3_hmac(&self.k, &Secret([&self.v[..], &[sep], provided_data].concat())));
+      self.v = Secret(sm3_hmac(&self.k, &self.v));
+      if provided_data.is_empty() {
+        break;
+      }
+    }
+  }
+
+  fn instantiate_algorithm(&mut self, seed_material: &[u8]) {
+    self.k = Secret(vec![0x00; 32]);
+    self.v = Secret(vec![0x01; 32]);
+    self.update(seed_material);
+    self.reseed_counter = 1;
+  }
+
+  fn reseed_algorithm(&mut self, entropy_input: &[u8], additional_input: &[u8]) {
+    self.update(&Secret([entropy_input, additional_input].concat()));
+    self.reseed_counter = 1;
+  }
+
+  fn generate_algorithm(&mut self, out: &mut [u8], additional_input: &[u8]) {
+    if !additional_input.is_empty() {
+      self.update(additional_input);
+    }
+
+    for outi in out.chunks_mut(32) {
+      self.v = Secret(sm3_hmac(&self.k, &self.v));
+      outi.copy_from_slice(&self.v[..outi.len()]);
+    }
+
+    self.update(additional_input);
+    self.reseed_counter += 1;
+  }
+}
+
+
+/// 为两种DRBG实现相同的实例化、重播种、生成流程及`RngCore`接口
+macro_rules! impl_sm3_drbg {
+  ($($drbg:ident { $($state:ident),* }),*) => {
+    $(
+      impl $drbg<OsRng> {
+        /// 以操作系统随机源作为熵源实例化DRBG，不启用预测抵抗
+        /// ## Parameters
+        /// - personalization: 个性化串，可为空
+        /// ## Returns
+        /// - DRBG实例，熵源失败则返回Sm3Error
+        pub fn new(personalization: &[u8]) -> Result<Self, Sm3Error> {
+          Self::with_entropy_source(OsRng, personalization, false)
+        }
+      }
+
+
+      impl<E: RngCore> $drbg<E> {
+        /// 以指定熵源实例化DRBG，依次读取熵输入与随机数nonce
+        /// ## Parameters
+        /// - entropy_source: 熵源
+        /// - personalization: 个性化串，可为空
+        /// - prediction_resistance: 是否启用预测抵抗，启用后每次生成前都从熵源重播种
+        /// ## Returns
+        /// - DRBG实例，熵源失败则返回Sm3Error
+        pub fn with_entropy_source(
+          entropy_source: E, personalization: &[u8], prediction_resistance: bool,
+        ) -> Result<Self, Sm3Error> {
+          let mut drbg = Self {
+            $($state: Secret(Vec::new()),)*
+            reseed_counter: 0,
+            reseed_interval: RESEED_INTERVAL,
+            prediction_resistance,
+            entropy_source,
+          };
+
+          let mut seed_material = Secret(vec![0u8; ENTROPY_LEN + NONCE_LEN]);
+          drbg.entropy_source.try_fill_bytes(&mut seed_material)
+            .map_err(|_| Sm3Error::EntropyError)?;
+          seed_material.extend_from_slice(personalization);
+          drbg.instantiate_algorithm(&seed_material);
+          Ok(drbg)
+        }
+
+        /// 设置是否启用预测抵抗
+        pub fn set_prediction_resistance(&mut self, prediction_resistance: bool) {
+          self.prediction_resistance = prediction_resistance;
+        }
+
+        /// 设置重播种间隔，超过该生成次数后自动从熵源重播种
+        /// ## Parameters
+        /// - reseed_interval: 重播种间隔，取值范围[1, 2^48]
+        pub fn set_reseed_interval(&mut self, reseed_interval: u64) {
+          self.reseed_interval = reseed_interval.clamp(1, RESEED_INTERVAL);
+        }
+
+        /// 从熵源重播种
+        /// ## Parameters
+        /// - additional_input: 附加输入，可为空
+        /// ## Returns
+        /// - 熵源失败则返回Sm3Error
+        pub fn reseed(&mut self, additional_input: &[u8]) -> Result<(), Sm3Error> {
+          self.try_reseed(additional_input).map_err(|_| Sm3Error::EntropyError)
+        }
+
+        /// 生成伪随机字节
+        /// ## Parameters
+        /// - out: 输出缓冲区，长度不超过MAX_REQUEST_LEN
+        /// - additional_input: 附加输入，可为空
+        /// ## Returns
+        /// - 请求过长或熵源失败则返回Sm3Error
+        pub fn generate(&mut self, out: &mut [u8], additional_input: &[u8]) -> Result<(), Sm3Error> {
+          if out.len() > MAX_REQUEST_LEN {
+            return Err(Sm3Error::RequestTooLarge);
+          }
+          self.try_generate(out, additional_input).map_err(|_| Sm3Error::EntropyError)
+        }
+
+        fn try_reseed(&mut self, additional_input: &[u8]) -> Result<(), rand::Error> {
+          let mut entropy_input = Secret(vec![0u8; ENTROPY_LEN]);
+          self.entropy_source.try_fill_bytes(&mut entropy_input)?;
+          self.reseed_algorithm(&entropy_input, additional_input);
+          Ok(())
+        }
+
+        fn try_generate(&mut self, out: &mut [u8], additional_input: &[u8]) -> Result<(), rand::Error> {
+          // 预测抵抗或达到重播种间隔时，附加输入在重播种中使用，生成时不再重复使用
+          if self.prediction_resistance || self.reseed_counter > self.reseed_interval {
+            self.try_reseed(additional_input)?;
+            self.generate_algorithm(out, &[]);
+          } else {
+            self.generate_algorithm(out, additional_input);
+          }
+          Ok(())
+        }
+      }
+
+
+      impl<E: RngCore> RngCore for $drbg<E> {
+        fn next_u32(&mut self) -> u32 {
+          let mut byt4 = [0u8; 4];
+          self.fill_bytes(&mut byt4);
+          u32::from_be_bytes(byt4)
+        }
+
+        fn next_u64(&mut self) -> u64 {
+          let mut byt8 = [0u8; 8];
+          self.fill_bytes(&mut byt8);
+          u64::from_be_bytes(byt8)
+        }
+
+        fn fill_bytes(&mut self, dest: &mut [u8]) {
+          self.try_fill_bytes(dest).expect("SM3 DRBG entropy source failed");
+        }
+
+        fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+          for desti in dest.chunks_mut(MAX_REQUEST_LEN) {
+            self.try_generate(desti, &[])?;
+          }
+          Ok(())
+        }
+      }
+
+
+      /// 熵源本身须为密码学安全的随机数发生器
+      impl<E: RngCore + CryptoRng> CryptoRng for $drbg<E> {}
+    )*
+  };
+}
+
+impl_sm3_drbg!(Sm3HashDrbg { v, c }, Sm3HmacDrbg { k, v });
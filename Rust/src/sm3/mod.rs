@@ -1,2 +1,4 @@
+#[allow(clippy::module_inception)]
 pub mod sm3;
-pub mod achieve;
\ No newline at end of file
+pub mod achieve;
+pub mod drbg;
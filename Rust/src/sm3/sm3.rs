@@ -10,6 +10,12 @@ pub enum Sm3Error {
   InvalidKey,
   // 不支持的SM3模式
   UnsupportedMode,
+  // 熵源获取失败
+  EntropyError,
+  // 单次请求的随机字节过长
+  RequestTooLarge,
+  // 域分隔标签为空
+  InvalidDst,
 }
 
 
@@ -22,6 +28,13 @@ pub enum Sm3ModeKind {
 pub struct Sm3;
 
 
+impl Default for Sm3 {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+
 impl Sm3 {
   pub fn new() -> Self {
     Self
@@ -1,4 +1,5 @@
 pub mod util;
+#[allow(clippy::module_inception)]
 mod sm4;
 
 pub use sm4::{Sm4ModeKind, Sm4Error, Sm4PaddingKind, Sm4, Sm4CryptoTrait};
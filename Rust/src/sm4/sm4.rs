@@ -1,3 +1,4 @@
+use crate::secret::Secret;
 use crate::sm4::util::*;
 
 
@@ -141,11 +142,12 @@ fn sms4_crypt(blk: &[u8], rk: &[u32]) -> Result<[u8; BLOCK], Sm4Error> {
 /// - crypt_kind: 加密还是解密
 /// ## Returns
 /// 32个32比特的轮密钥
-fn sms4_key_ext(mk: &[u8], crypt_kind: &Sm4CryptoKind) -> Result<Vec<u32>, Sm4Error> {
-  let words: [u32; 4] = to_words(mk)?;
-  let mut words: Vec<u32> =
-    vec![words[0] ^ 0xa3b1bac6, words[1] ^ 0x56aa3350, words[2] ^ 0x677d9197, words[3] ^ 0xb27022dc];
-  let mut rks: Vec<u32> = Vec::with_capacity(32);
+fn sms4_key_ext(mk: &[u8], crypt_kind: &Sm4CryptoKind) -> Result<Secret<Vec<u32>>, Sm4Error> {
+  let mk_words: [u32; 4] = to_words(mk)?;
+  let mut words: Secret<Vec<u32>> = Secret(vec![
+    mk_words[0] ^ 0xa3b1bac6, mk_words[1] ^ 0x56aa3350, mk_words[2] ^ 0x677d9197, mk_words[3] ^ 0xb27022dc,
+  ]);
+  let mut rks: Secret<Vec<u32>> = Secret(Vec::with_capacity(ROUND));
 
   for i in 0..ROUND {
     let rki: u32 = words[i] ^ l2(byte_sub(words[i + 1] ^ words[i + 2] ^ words[i + 3] ^ CK[i]));
@@ -166,7 +168,7 @@ fn sms4_key_ext(mk: &[u8], crypt_kind: &Sm4CryptoKind) -> Result<Vec<u32>, Sm4Er
 /// - sm4_key: 128比特的SM4主密钥
 #[derive(Debug)]
 struct Sm4Params {
-  sm4_key: Vec<u8>,
+  sm4_key: Secret<Vec<u8>>,
   mode: Sm4ModeKind,
   padding: Option<Sm4PaddingKind>,
   iv: Option<Vec<u8>>,
@@ -183,7 +185,7 @@ impl Sm4Params {
   pub fn new<T: ConvertByteArr>(
     sm4_key: T, mode: Sm4ModeKind, padding: Option<Sm4PaddingKind>, iv: Option<T>,
   ) -> Result<Self, Sm4Error> {
-    let sm4_key = sm4_key.convert_to_byte_arrs(EnDecodingKind::Hex)?;
+    let sm4_key = Secret(sm4_key.convert_to_byte_arrs(EnDecodingKind::Hex)?);
     if sm4_key.len() != BLOCK {
       return Err(Sm4Error::InvalidKey);
     }
@@ -219,6 +221,13 @@ impl Sm4Params {
 pub struct Sm4;
 
 
+impl Default for Sm4 {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+
 impl Sm4 {
   pub fn new() -> Self {
     Self
@@ -237,7 +246,7 @@ impl Sm4 {
 
     if sm4_params.padding.is_some() && cp_kind != Sm4CryptoKind::Decrypt {
       let padl = BLOCK - (arrs.len() % BLOCK);
-      arrs.extend(std::iter::repeat(padl as u8).take(padl));
+      arrs.extend(std::iter::repeat_n(padl as u8, padl));
     }
 
     for arri in arrs.chunks_exact(BLOCK) {
@@ -0,0 +1,38 @@
+use rand::{CryptoRng, Error, RngCore};
+
+
+/// 依次输出给定字节的随机数发生器，用于复现标准示例和DRBG的已知答案；
+/// 字节耗尽时try_fill_bytes返回错误，fill_bytes直接panic
+pub struct FixedRng(pub Vec<u8>);
+
+
+impl RngCore for FixedRng {
+  fn next_u32(&mut self) -> u32 {
+    let mut byt4 = [0u8; 4];
+    self.fill_bytes(&mut byt4);
+    u32::from_be_bytes(byt4)
+  }
+
+  fn next_u64(&mut self) -> u64 {
+    let mut byt8 = [0u8; 8];
+    self.fill_bytes(&mut byt8);
+    u64::from_be_bytes(byt8)
+  }
+
+  fn fill_bytes(&mut self, dest: &mut [u8]) {
+    self.try_fill_bytes(dest).expect("FixedRng exhausted");
+  }
+
+  fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), Error> {
+    if self.0.len() < dest.len() {
+      return Err(Error::new("FixedRng exhausted"));
+    }
+    let rest = self.0.split_off(dest.len());
+    dest.copy_from_slice(&self.0);
+    self.0 = rest;
+    Ok(())
+  }
+}
+
+
+impl CryptoRng for FixedRng {}
@@ -1,5 +1,14 @@
 // cargo test --test sm3 -- --show-output
 
+#![allow(deprecated)]
+
+mod common;
+
+use common::FixedRng;
+use rand::RngCore;
+use sm_crypto::sm2::sm2::*;
+use sm_crypto::sm3::achieve::{sm3_digest, sm3_expand_message_xmd, sm3_pbkdf2, Sm3Hasher};
+use sm_crypto::sm3::drbg::*;
 use sm_crypto::sm3::sm3::*;
 
 
@@ -39,3 +48,196 @@ fn test_sm3_hash_with_hmac() {
     "6693881715637cc7f347bc7cdb5bdd86f65c3076388bf45f84b1ac276a647095"
   );
 }
+
+
+#[test]
+fn test_sm3_pbkdf2() {
+  // 结果与Python hashlib.pbkdf2_hmac("sm3", ...)一致
+  let dk = sm3_pbkdf2(b"password", b"salt", 4096, 32);
+  println!("dk: {}", hex::encode(&dk));
+  assert_eq!(hex::encode(&dk), "b6e8f2074c87432b78f62e5ced980fdff89e86af2f693dab1638e2b3683045dd");
+
+  // 跨越多个分组
+  let dk = sm3_pbkdf2(b"password", b"salt", 1, 40);
+  assert_eq!(
+    hex::encode(&dk),
+    "4612f922a1fdcefaf4312fc6f8f3322b489cbf24f2ea361b44c2bd8fa2c6dcb0e2bea084418ffb4f"
+  );
+}
+
+
+#[test]
+fn test_sm3_expand_message_xmd() {
+  // 结果与按RFC 9380第5.3.1节逐步计算的Python实现一致
+  let dst = b"QUUX-V01-CS02-with-expander-SM3-128";
+  let q128 = [b"q128_".as_slice(), &[b'q'; 128]].concat();
+  for (msg, len_in_bytes, uniform_hex) in [
+    (b"".as_slice(), 32, "e778160e0257636c7fda69bd61ec1b1be3a6514082a63c5c84109f49d5411e29"),
+    (b"abc", 32, "bc947b2bab2f347c366cdd414e278bb80b176a0a3dde02088be71fcbf8660603"),
+    (&q128, 32, "13aaeba26b04def3b0c633eacd1f459cdc1132a90ccf19adbfe647eb37905d79"),
+    (b"abc", 128, "4ed8c78f116ffeb01451a0d7770e74370493b57c3f3001b48b18aae191f4401b\
+      97d99142f0d5c4f4b489b6fc4ca4a79572342d4e9d8674c651da615654af0d1d\
+      57cca6398777e913b5566fd2e9921ac8b7d2a16924f42ebec71f3bc2d1a552fe\
+      de37a431445af6e24433448059a6e5a6b84ed9bc6ecebacae1f75c225ce09322"),
+  ] {
+    assert_eq!(hex::encode(sm3_expand_message_xmd(msg, dst, len_in_bytes).unwrap()), uniform_hex);
+  }
+  // 超过255字节的域分隔标签先杂凑
+  assert_eq!(
+    hex::encode(sm3_expand_message_xmd(b"abc", &[b'x'; 300], 32).unwrap()),
+    "623891d35b8922e2979fdc3845cb26ca44dacf2908b9f12789e7d86a4b30dabb"
+  );
+  // 输出长度不是分组长度的整数倍时截断
+  assert_eq!(sm3_expand_message_xmd(b"abc", dst, 48).unwrap().len(), 48);
+
+  assert_eq!(sm3_expand_message_xmd(b"abc", b"", 32), Err(Sm3Error::InvalidDst));
+  assert_eq!(sm3_expand_message_xmd(b"abc", dst, 255 * 32 + 1), Err(Sm3Error::RequestTooLarge));
+  assert_eq!(sm3_expand_message_xmd(b"abc", dst, 255 * 32).unwrap().len(), 255 * 32);
+}
+
+
+#[test]
+fn test_sm3_digest_padding() {
+  // 填充0x80后超过56字节，需要新补一个分组
+  let sm3_hash_reap = hex::encode(sm3_digest(&[b'a'; 60]));
+  assert_eq!(sm3_hash_reap, "77008622f6a713b2f6728ba8234012e8d4c99c9d63fd4ac954a2ce6a3afe4bc6");
+  let sm3_hash_reap = hex::encode(sm3_digest(&[b'a'; 300]));
+  assert_eq!(sm3_hash_reap, "06ea971838e9ea6603795167c74ccbf8c3081a88355bd10c0a0f285d6343e5bd");
+}
+
+
+#[test]
+fn test_sm3_hasher() {
+  // 任意切分输入，结果与一次性杂凑相同
+  let plain_arrs: Vec<u8> = (0..1000u32).map(|i| i as u8).collect();
+  for chunkl in [1, 7, 55, 56, 63, 64, 65, 200] {
+    let mut sm3_hasher = Sm3Hasher::new();
+    for plain_chunk in plain_arrs.chunks(chunkl) {
+      sm3_hasher.update(plain_chunk);
+    }
+    assert_eq!(sm3_hasher.finalize(), sm3_digest(&plain_arrs));
+  }
+
+  let mut sm3_hasher = Sm3Hasher::new();
+  sm3_hasher.update(b"hello ");
+  sm3_hasher.update(b"");
+  sm3_hasher.update(b"world");
+  assert_eq!(
+    hex::encode(sm3_hasher.finalize()),
+    "44f0061e69fa6fdfc290c494654a05dc0c053da7e5c52b84ef93a9d67d3fff88"
+  );
+}
+
+
+/// 熵输入0x00..0x1f、nonce 0x20..0x2f，之后两次重播种的熵输入为0x30..0x4f、0x50..0x6f
+fn drbg_entropy() -> FixedRng {
+  FixedRng((0u8..112).collect())
+}
+
+
+#[test]
+fn test_sm3_hash_drbg() {
+  // 结果与按SP 800-90A以SM3独立实现的参考程序一致
+  let mut out = [0u8; 64];
+  let mut drbg = Sm3HashDrbg::with_entropy_source(drbg_entropy(), b"sm-crypto", false).unwrap();
+  drbg.generate(&mut out, &[]).unwrap();
+  drbg.generate(&mut out, &[]).unwrap();
+  println!("hash_drbg: {}", hex::encode(out));
+  assert_eq!(
+    hex::encode(out),
+    "2bbb669421c102d2be89e863bec197312681e496a2a6e6e5f48c28670b0b8f78\
+     2bcea2392539ced263a42d36660bcb655b99afc2581134594bc9d7923c858f92"
+  );
+
+  // 附加输入与显式重播种
+  let mut drbg = Sm3HashDrbg::with_entropy_source(drbg_entropy(), b"sm-crypto", false).unwrap();
+  drbg.generate(&mut out, b"add1").unwrap();
+  drbg.reseed(b"reseed").unwrap();
+  drbg.generate(&mut out, b"add2").unwrap();
+  assert_eq!(
+    hex::encode(out),
+    "85c454623df628d9ac6bd5dfe653be52586a74599d9fcf94705ca31292d38cc2\
+     8c851fa5a039ec5f0ac8cacbcc863af0e04cc8757b43d78e767de3c6ec00ec9f"
+  );
+
+  // 预测抵抗：每次生成前都从熵源重播种
+  let mut drbg = Sm3HashDrbg::with_entropy_source(drbg_entropy(), b"sm-crypto", true).unwrap();
+  drbg.generate(&mut out, b"add1").unwrap();
+  drbg.generate(&mut out, b"add2").unwrap();
+  assert_eq!(
+    hex::encode(out),
+    "a6f504e74c4e302825b45d27c3b1c1adce156bfb455728fb32ee8eaef9a54f3e\
+     27279a941f250a43f70e0963bdd341192ae21e9513109c735fbeccdefb7729f1"
+  );
+  // 熵源耗尽
+  assert_eq!(drbg.generate(&mut out, &[]), Err(Sm3Error::EntropyError));
+}
+
+
+#[test]
+fn test_sm3_hmac_drbg() {
+  let mut out = [0u8; 64];
+  let mut drbg = Sm3HmacDrbg::with_entropy_source(drbg_entropy(), b"sm-crypto", false).unwrap();
+  drbg.generate(&mut out, &[]).unwrap();
+  drbg.generate(&mut out, &[]).unwrap();
+  println!("hmac_drbg: {}", hex::encode(out));
+  assert_eq!(
+    hex::encode(out),
+    "d56f1beb22ed2ea8c1ad40fe91c6756fa6e9a18160561e69312079263cef1c28\
+     afcc2a38f1ebe8841e370df9a92454c406c04a1eab1d7e70e9ecfebfccd57382"
+  );
+
+  let mut drbg = Sm3HmacDrbg::with_entropy_source(drbg_entropy(), b"sm-crypto", false).unwrap();
+  drbg.generate(&mut out, b"add1").unwrap();
+  drbg.reseed(b"reseed").unwrap();
+  drbg.generate(&mut out, b"add2").unwrap();
+  assert_eq!(
+    hex::encode(out),
+    "17c9822b499b8c0d7fb83efafe40cd26c1ea4ff17e56c4d5bd07e0eff27e456e\
+     8b4307c54afcbe07f77301aee2b39d62832480ad69a207936a385380b3464f65"
+  );
+
+  let mut drbg = Sm3HmacDrbg::with_entropy_source(drbg_entropy(), b"sm-crypto", true).unwrap();
+  drbg.generate(&mut out, b"add1").unwrap();
+  drbg.generate(&mut out, b"add2").unwrap();
+  assert_eq!(
+    hex::encode(out),
+    "9c6e4dc36ee27654d547f5ea2cf869e903decb16d68d9704d8a3b9a82e798bf1\
+     03b228e23a16e0b531419fba07a4a531aab8b169c2e24f4864fe28372622a4c4"
+  );
+}
+
+
+#[test]
+fn test_sm3_drbg_as_rng() {
+  // 达到重播种间隔后自动从熵源重播种，与显式重播种的输出一致
+  let mut out = [0u8; 64];
+  let mut drbg = Sm3HmacDrbg::with_entropy_source(drbg_entropy(), b"", false).unwrap();
+  drbg.set_reseed_interval(1);
+  drbg.generate(&mut out, &[]).unwrap();
+  drbg.generate(&mut out, &[]).unwrap();
+  let mut expected = [0u8; 64];
+  let mut drbg = Sm3HmacDrbg::with_entropy_source(drbg_entropy(), b"", false).unwrap();
+  drbg.generate(&mut expected, &[]).unwrap();
+  drbg.reseed(&[]).unwrap();
+  drbg.generate(&mut expected, &[]).unwrap();
+  assert_eq!(out, expected);
+
+  // 单次请求过长时generate报错，作为RngCore使用时自动分段
+  let mut drbg = Sm3HashDrbg::new(b"sm-crypto").unwrap();
+  let mut long_out = vec![0u8; MAX_REQUEST_LEN + 1];
+  assert_eq!(drbg.generate(&mut long_out, &[]), Err(Sm3Error::RequestTooLarge));
+  drbg.fill_bytes(&mut long_out);
+
+  // 作为SM2的随机数发生器
+  let sm2_obj = Sm2::with_rng(Sm3HmacDrbg::new(b"sm2").unwrap());
+  let private_key = sm2_obj.generate_private_key().unwrap();
+  let public_key = private_key.public_key().clone();
+  let sm2_obj = Sm2::with_rng(Sm3HashDrbg::new(b"sm2").unwrap());
+  let sign = sm2_obj.sign("hello world", &private_key, true, true, None, None).unwrap();
+  assert!(sm2_obj.verify("hello world", &sign, &public_key, true, true, None).unwrap());
+  // 熵源标记为CryptoRng时DRBG才是CryptoRng
+  let sm2_obj = Sm2::with_rng(Sm3HmacDrbg::with_entropy_source(drbg_entropy(), b"sm2", false).unwrap());
+  let sign = sm2_obj.sign("hello world", &private_key, true, true, None, None).unwrap();
+  assert!(sm2_obj.verify("hello world", &sign, &public_key, true, true, None).unwrap());
+}
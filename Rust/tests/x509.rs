@@ -0,0 +1,248 @@
+// cargo test --test x509 -- --show-output
+
+#![allow(deprecated)]
+
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use sm_crypto::sm2::sm2::*;
+use sm_crypto::sm2::x509::*;
+
+
+// 由OpenSSL生成：自签名根证书 -> 中间CA（pathlen:0） -> 终端证书，均为SM2-with-SM3签名，有效期至2126年
+const ROOT_PEM: &str = "\
+-----BEGIN CERTIFICATE-----
+MIIBtDCCAVmgAwIBAgIBATAKBggqgRzPVQGDdTBAMQswCQYDVQQGEwJDTjEWMBQG
+A1UECgwNUHJpc2NpcmEgVGVzdDEZMBcGA1UEAwwQUHJpc2NpcmEgUm9vdCBDQTAg
+Fw0yNjEwMTkwNjQ3MjhaGA8yMTI2MDkyNTA2NDcyOFowQDELMAkGA1UEBhMCQ04x
+FjAUBgNVBAoMDVByaXNjaXJhIFRlc3QxGTAXBgNVBAMMEFByaXNjaXJhIFJvb3Qg
+Q0EwWTATBgcqhkjOPQIBBggqgRzPVQGCLQNCAAStGoe5cyzyFnQzfg/1yircRl98
+CRLO01J6hEDHsSi6AAj9AUQd5Rp/QprjVubwYwFmo5kpRLfgFuOoB5Z5eIlvo0Iw
+QDAPBgNVHRMBAf8EBTADAQH/MA4GA1UdDwEB/wQEAwIBBjAdBgNVHQ4EFgQU+KuK
+pQdCJYoVMGRoi9jPELBwGBcwCgYIKoEcz1UBg3UDSQAwRgIhAOWKXlvTpwq7q8KA
+eXLsmR9TT2caa62Wog/OtXRR7vhIAiEAmSZpGKroKcgJhNdeMAWKMOkx4sunyg7p
+tE7k8XodIvY=
+-----END CERTIFICATE-----
+";
+
+const CA_PEM: &str = "\
+-----BEGIN CERTIFICATE-----
+MIIB2TCCAYCgAwIBAgIBAjAKBggqgRzPVQGDdTBAMQswCQYDVQQGEwJDTjEWMBQG
+A1UECgwNUHJpc2NpcmEgVGVzdDEZMBcGA1UEAwwQUHJpc2NpcmEgUm9vdCBDQTAg
+Fw0yNjEwMTkwNjQ3NDBaGA8yMTI2MDkyNTA2NDc0MFowQzELMAkGA1UEBhMCQ04x
+FjAUBgNVBAoMDVByaXNjaXJhIFRlc3QxHDAaBgNVBAMME1ByaXNjaXJhIElzc3Vp
+bmcgQ0EwWTATBgcqhkjOPQIBBggqgRzPVQGCLQNCAAT36BTAzrN3MYpka4vjVWms
+5+g66J6WvMxHSlTYmNDOixdxxOI5xdhKsxqtE3MkCSoDEs2h/TZjacss2ooBB8N4
+o2YwZDASBgNVHRMBAf8ECDAGAQH/AgEAMA4GA1UdDwEB/wQEAwIBBjAdBgNVHQ4E
+FgQUWpGwdGJi/3Uccq9PLgwbLP5HY2IwHwYDVR0jBBgwFoAU+KuKpQdCJYoVMGRo
+i9jPELBwGBcwCgYIKoEcz1UBg3UDRwAwRAIgXMUty1HpfZBa1cGGQIfLIiKcekqF
+KwB86fUL6P5X1qgCIFDWvefEVKnhyyeaKom4b7AiV4RwBY6KHs+oycMlgrcW
+-----END CERTIFICATE-----
+";
+
+const LEAF_PEM: &str = "\
+-----BEGIN CERTIFICATE-----
+MIICFjCCAbugAwIBAgIEChssPTAKBggqgRzPVQGDdTBDMQswCQYDVQQGEwJDTjEW
+MBQGA1UECgwNUHJpc2NpcmEgVGVzdDEcMBoGA1UEAwwTUHJpc2NpcmEgSXNzdWlu
+ZyBDQTAgFw0yNjEwMTkwNjQ3NDBaGA8yMTI2MDkyNTA2NDc0MFowQjELMAkGA1UE
+BhMCQ04xFjAUBgNVBAoMDVByaXNjaXJhIFRlc3QxGzAZBgNVBAMMEmNsaWVudC5l
+eGFtcGxlLmNvbTBZMBMGByqGSM49AgEGCCqBHM9VAYItA0IABD9ocwodhRcvf31T
+7DmNdiGc637olQP/taHLjXvvHvs5G3YjoT5VkuRtM7udJkRNmwYEczflJCeM7nwZ
+QkZl0iajgZswgZgwDAYDVR0TAQH/BAIwADAOBgNVHQ8BAf8EBAMCBaAwEwYDVR0l
+BAwwCgYIKwYBBQUHAwIwIwYDVR0RBBwwGoISY2xpZW50LmV4YW1wbGUuY29thwQK
+AAABMB8GA1UdIwQYMBaAFFqRsHRiYv91HHKvTy4MGyz+R2NiMB0GA1UdDgQWBBTp
+53ADXhbNssfhTR6+EhDtb+SzYDAKBggqgRzPVQGDdQNJADBGAiEAzojLXxDlQb3Y
+S/tXkPRF2aHdkApJO5xdnfIRPp68r/ACIQCfCtiEDI6KxRnFcA+/v8DSu/wb6vai
+Nf88eGf/gtdMmA==
+-----END CERTIFICATE-----
+";
+
+
+/// 2030-01-01T00:00:00Z
+fn verify_time() -> SystemTime {
+  UNIX_EPOCH + Duration::from_secs(1893456000)
+}
+
+
+#[test]
+fn test_parse_certificate() {
+  let sm2_obj = Sm2::new();
+  let leaf = Sm2Certificate::from_pem(&sm2_obj, LEAF_PEM).unwrap();
+  println!("subject = {}", leaf.subject());
+  println!("issuer = {}", leaf.issuer());
+  assert_eq!(leaf.version(), 3);
+  assert_eq!(leaf.serial_number_hex(), "0a1b2c3d");
+  assert_eq!(leaf.subject(), "C=CN, O=Priscira Test, CN=client.example.com");
+  assert_eq!(leaf.issuer(), "C=CN, O=Priscira Test, CN=Priscira Issuing CA");
+  assert!(!leaf.is_ca());
+  assert!(!leaf.is_self_issued());
+  assert!(leaf.is_valid_at(verify_time()));
+  assert!(!leaf.is_valid_at(UNIX_EPOCH + Duration::from_secs(1577836800)));
+  assert_eq!(leaf.public_key().to_hex().len(), 130);
+
+  let root = Sm2Certificate::from_pem(&sm2_obj, ROOT_PEM).unwrap();
+  assert!(root.is_ca());
+  assert!(root.is_self_issued());
+  assert_eq!(root.to_pem().unwrap(), ROOT_PEM);
+  assert_eq!(Sm2Certificate::from_der(&sm2_obj, root.to_der()).unwrap().subject(), root.subject());
+
+  // 非证书
+  assert_eq!(
+    Sm2Certificate::from_pem(&sm2_obj, &root.public_key().to_public_key_pem().unwrap()).unwrap_err(),
+    Sm2Error::Asn1Error
+  );
+}
+
+
+#[test]
+fn test_verify_certificate_signature() {
+  let sm2_obj = Sm2::new();
+  let root = Sm2Certificate::from_pem(&sm2_obj, ROOT_PEM).unwrap();
+  let ca = Sm2Certificate::from_pem(&sm2_obj, CA_PEM).unwrap();
+  let leaf = Sm2Certificate::from_pem(&sm2_obj, LEAF_PEM).unwrap();
+
+  assert!(root.verify_signature(&sm2_obj, root.public_key()).unwrap());
+  assert!(ca.verify_signature(&sm2_obj, root.public_key()).unwrap());
+  assert!(leaf.verify_signature(&sm2_obj, ca.public_key()).unwrap());
+  assert!(!leaf.verify_signature(&sm2_obj, root.public_key()).unwrap());
+  assert!(leaf.verify_issued_by(&sm2_obj, &ca).unwrap());
+  assert!(!leaf.verify_issued_by(&sm2_obj, &root).unwrap());
+  // 终端证书不能签发证书
+  assert!(!ca.verify_issued_by(&sm2_obj, &leaf).unwrap());
+
+  // 篡改TBSCertificate中的序列号
+  let mut der_arrs = leaf.to_der().to_vec();
+  let serial_idx = der_arrs.windows(4).position(|w| w == [0x0a, 0x1b, 0x2c, 0x3d]).unwrap();
+  der_arrs[serial_idx] = 0x0b;
+  let tampered = Sm2Certificate::from_der(&sm2_obj, &der_arrs).unwrap();
+  assert!(!tampered.verify_signature(&sm2_obj, ca.public_key()).unwrap());
+}
+
+
+#[test]
+fn test_verify_certificate_chain() {
+  let sm2_obj = Sm2::new();
+  let root = Sm2Certificate::from_pem(&sm2_obj, ROOT_PEM).unwrap();
+  let ca = Sm2Certificate::from_pem(&sm2_obj, CA_PEM).unwrap();
+  let leaf = Sm2Certificate::from_pem(&sm2_obj, LEAF_PEM).unwrap();
+
+  let intermediates = vec![ca.clone()];
+  let mut trust_store = Sm2TrustStore::new();
+  trust_store.add_root(root.clone());
+  let chain = trust_store.verify_chain(&sm2_obj, &leaf, &intermediates, verify_time()).unwrap();
+  let chain_subjects = chain.iter().map(|cert| cert.subject()).collect::<Vec<_>>();
+  println!("chain = {:?}", chain_subjects);
+  assert_eq!(chain_subjects, vec![leaf.subject(), ca.subject(), root.subject()]);
+
+  // 中间CA直接签发的证书，以及根证书本身
+  assert_eq!(trust_store.verify_chain(&sm2_obj, &ca, &[], verify_time()).unwrap().len(), 2);
+  assert_eq!(trust_store.verify_chain(&sm2_obj, &root, &[], verify_time()).unwrap().len(), 1);
+
+  // 缺少中间证书
+  assert_eq!(
+    trust_store.verify_chain(&sm2_obj, &leaf, &[], verify_time()).unwrap_err(),
+    Sm2Error::UntrustedCertificate
+  );
+  // 不在有效期内
+  assert_eq!(
+    trust_store.verify_chain(&sm2_obj, &leaf, &intermediates, UNIX_EPOCH + Duration::from_secs(1577836800))
+      .unwrap_err(),
+    Sm2Error::CertificateExpired
+  );
+  // 根证书不受信任
+  let trust_store = Sm2TrustStore::new();
+  assert_eq!(
+    trust_store.verify_chain(&sm2_obj, &leaf, &[ca, root], verify_time()).unwrap_err(),
+    Sm2Error::UntrustedCertificate
+  );
+}
+
+
+#[test]
+fn test_self_signed_certificate() {
+  let sm2_obj = Sm2::new();
+  let private_key = sm2_obj.generate_private_key().unwrap();
+  let mut params = Sm2CertificateParams::new("Priscira Test Root");
+  params.subject.insert(0, ("C".to_string(), "CN".to_string()));
+  params.subject.insert(1, ("O".to_string(), "测试机构".to_string()));
+  params.serial_number = Some(vec![0x00, 0x80, 0x01]);
+  params.not_before = UNIX_EPOCH + Duration::from_secs(1767225600);
+  // 2050年之后使用GeneralizedTime
+  params.not_after = UNIX_EPOCH + Duration::from_secs(2556144000);
+  params.is_ca = true;
+  params.path_len = Some(1);
+  params.key_usages = vec![Sm2KeyUsage::KeyCertSign, Sm2KeyUsage::CrlSign];
+
+  let root = Sm2Certificate::self_signed(&sm2_obj, &params, &private_key).unwrap();
+  println!("root = {}", root.to_pem().unwrap());
+  assert_eq!(root.subject(), "C=CN, O=测试机构, CN=Priscira Test Root");
+  assert_eq!(root.issuer(), root.subject());
+  assert_eq!(root.serial_number_hex(), "008001");
+  assert_eq!(root.not_before(), params.not_before);
+  assert_eq!(root.not_after(), params.not_after);
+  assert_eq!(root.public_key(), private_key.public_key());
+  assert!(root.is_ca());
+  assert_eq!(root.key_usages().unwrap(), vec![Sm2KeyUsage::KeyCertSign, Sm2KeyUsage::CrlSign]);
+  assert!(root.subject_alt_names().unwrap().is_empty());
+  assert!(root.verify_signature(&sm2_obj, private_key.public_key()).unwrap());
+
+  // 自签名证书可直接作为根证书
+  let mut trust_store = Sm2TrustStore::new();
+  trust_store.add_root(root.clone());
+  assert_eq!(trust_store.verify_chain(&sm2_obj, &root, &[], verify_time()).unwrap().len(), 1);
+
+  // 终端证书：随机序列号、主体备用名称
+  let leaf_key = sm2_obj.generate_private_key().unwrap();
+  let mut params = Sm2CertificateParams::new("client.example.com");
+  params.subject_alt_names = vec![
+    Sm2SubjectAltName::Dns("client.example.com".to_string()),
+    Sm2SubjectAltName::Ip("10.0.0.1".parse().unwrap()),
+    Sm2SubjectAltName::Email("client@example.com".to_string()),
+  ];
+  params.key_usages = vec![Sm2KeyUsage::DigitalSignature, Sm2KeyUsage::KeyEncipherment];
+  let leaf = Sm2Certificate::self_signed(&sm2_obj, &params, &leaf_key).unwrap();
+  assert_eq!(leaf.serial_number_hex().len(), 32);
+  assert!(!leaf.is_ca());
+  assert_eq!(leaf.subject_alt_names().unwrap(), params.subject_alt_names);
+  assert_eq!(leaf.key_usages().unwrap(), params.key_usages);
+  assert!(leaf.is_valid_at(SystemTime::now()));
+  // 非CA证书即便受信任，也不能作为颁发者
+  assert!(!leaf.verify_issued_by(&sm2_obj, &leaf).unwrap());
+
+  // 非法参数
+  params.serial_number = Some(vec![0; 4]);
+  assert_eq!(Sm2Certificate::self_signed(&sm2_obj, &params, &leaf_key).unwrap_err(), Sm2Error::InvalidData);
+  params.serial_number = None;
+  params.subject.push(("X".to_string(), "unknown".to_string()));
+  assert_eq!(Sm2Certificate::self_signed(&sm2_obj, &params, &leaf_key).unwrap_err(), Sm2Error::InvalidData);
+}
+
+
+#[test]
+fn test_certificate_request() {
+  let sm2_obj = Sm2::new();
+  let private_key = sm2_obj.generate_private_key().unwrap();
+  let mut params = Sm2CertificateParams::new("client.example.com");
+  params.subject.insert(0, ("O".to_string(), "Priscira Test".to_string()));
+  params.subject.push(("2.5.4.5".to_string(), "SN0001".to_string()));
+  params.subject_alt_names = vec![Sm2SubjectAltName::Dns("client.example.com".to_string())];
+  params.key_usages = vec![Sm2KeyUsage::DigitalSignature];
+
+  let csr = Sm2CertificateRequest::new(&sm2_obj, &params, &private_key).unwrap();
+  let csr_pem = csr.to_pem().unwrap();
+  println!("csr = {}", csr_pem);
+  assert!(csr.verify(&sm2_obj).unwrap());
+
+  let csr = Sm2CertificateRequest::from_pem(&sm2_obj, &csr_pem).unwrap();
+  assert_eq!(csr.subject(), "O=Priscira Test, CN=client.example.com, serialNumber=SN0001");
+  assert_eq!(csr.public_key(), private_key.public_key());
+  assert_eq!(csr.subject_alt_names().unwrap(), params.subject_alt_names);
+  assert!(csr.verify(&sm2_obj).unwrap());
+
+  // 公钥与签名不匹配
+  let other_key = sm2_obj.generate_private_key().unwrap();
+  let mut der_arrs = csr.to_der().to_vec();
+  let pub_k_arrs = private_key.public_key().to_bytes();
+  let pub_k_idx = der_arrs.windows(pub_k_arrs.len()).position(|w| w == pub_k_arrs).unwrap();
+  der_arrs[pub_k_idx..pub_k_idx + 65].copy_from_slice(&other_key.public_key().to_bytes());
+  let forged_csr = Sm2CertificateRequest::from_der(&sm2_obj, &der_arrs).unwrap();
+  assert!(!forged_csr.verify(&sm2_obj).unwrap());
+}